@@ -0,0 +1,34 @@
+// algorithm15 longest_substring_without_repeating_chars 的输入生成器
+use super::Rng;
+use std::collections::HashSet;
+
+pub type Input = String;
+
+// n / 4 个互不相同的非 ASCII 字符随机排列后重复 4 次，最长无重复子串的长度随 n 增长，
+// 每次在窗口中查找重复字符的 O(n·k) 解法因此表现为 O(n^2)。
+// 按字节而不是按字符处理的解法窗口不会超过 256 个字节，但结果错误，由 expected 检出
+pub fn generate(n: u64, rng: &mut Rng) -> Input {
+    let distinct = (n / 4).max(1) as u32;
+    let mut alphabet: Vec<char> = (0..distinct)
+        .map(|i| {
+            // 从 U+0100 开始，跳过代理区 U+D800..U+DFFF
+            let code = 0x100 + i;
+            let code = if code >= 0xD800 { code + 0x800 } else { code };
+            char::from_u32(code).expect("valid scalar value")
+        })
+        .collect();
+    for i in (1..alphabet.len()).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        alphabet.swap(i, j);
+    }
+    alphabet.iter().cycle().take(n as usize).collect()
+}
+
+// 整个字母表循环重复，任意连续的一轮都互不相同，最长无重复子串即为字母表的大小
+pub fn expected(input: &Input) -> Option<i32> {
+    Some(input.chars().collect::<HashSet<char>>().len() as i32)
+}
+
+pub fn run(input: &Input) -> i32 {
+    crate::longest_substring_without_repeating_chars(input.clone())
+}
@@ -0,0 +1,21 @@
+// algorithm19 fib 的输入生成器
+use super::Rng;
+
+pub type Input = i32;
+
+// fib(46) 之后超出 i32 范围，规模需保持在 46 以内
+pub fn generate(n: u64, _rng: &mut Rng) -> Input {
+    n as i32
+}
+
+pub fn expected(input: &Input) -> Option<i32> {
+    let (mut a, mut b) = (0i32, 1i32);
+    for _ in 0..*input {
+        (a, b) = (b, a.wrapping_add(b));
+    }
+    Some(a)
+}
+
+pub fn run(input: &Input) -> i32 {
+    crate::fib(*input)
+}
@@ -0,0 +1,45 @@
+// algorithm18 merge_intervals 的对照实现与输入生成器
+use super::Rng;
+
+pub type Input = Vec<Vec<i32>>;
+
+// 随机生成最多 12 个非负区间
+pub fn generate(rng: &mut Rng) -> Input {
+    let len = rng.below(13) as usize;
+    (0..len)
+        .map(|_| {
+            let start = rng.range(0, 50);
+            let end = start + rng.range(0, 10);
+            vec![start, end]
+        })
+        .collect()
+}
+
+pub fn candidate(input: &Input) -> Vec<Vec<i32>> {
+    crate::merge_intervals(input.clone())
+}
+
+pub fn reference(input: &Input) -> Vec<Vec<i32>> {
+    let mut intervals: Vec<(i32, i32)> = input.iter().map(|v| (v[0], v[1])).collect();
+    intervals.sort();
+
+    let mut merged: Vec<Vec<i32>> = Vec::new();
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last[1] => last[1] = last[1].max(end),
+            _ => merged.push(vec![start, end]),
+        }
+    }
+    merged
+}
+
+// 依次尝试去掉一个区间
+pub fn shrink(input: &Input) -> Vec<Input> {
+    (0..input.len())
+        .map(|i| {
+            let mut smaller = input.clone();
+            smaller.remove(i);
+            smaller
+        })
+        .collect()
+}
@@ -3,7 +3,9 @@
     This problem requires you to merge two ordered singly linked lists into one ordered singly linked list
 */
 
-use std::fmt::{self, Display, Formatter};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
 use std::ptr::NonNull;
 use std::vec::*;
 
@@ -18,13 +20,20 @@ impl<T> Node<T> {
         Node { val: t, next: None }
     }
 }
-#[derive(Debug)]
+
+// 单链表：start 指向第一个节点，end 指向最后一个节点，节点由链表独占并在 Drop 时释放
 struct LinkedList<T> {
     length: u32,
     start: Option<NonNull<Node<T>>>,
     end: Option<NonNull<Node<T>>>,
+    // 告诉编译器链表拥有 Box<Node<T>>，以便正确进行 drop 检查
+    marker: PhantomData<Box<Node<T>>>,
 }
 
+// 链表独占其节点，与 Box<Node<T>> 一样可以在线程间转移和共享
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
 impl<T> Default for LinkedList<T> {
     fn default() -> Self {
         Self::new()
@@ -37,13 +46,25 @@ impl<T> LinkedList<T> {
             length: 0,
             start: None,
             end: None,
+            marker: PhantomData,
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start.is_none()
+    }
+
+    // 在尾部追加，与 push_back 相同
     pub fn add(&mut self, obj: T) {
-        let mut node = Box::new(Node::new(obj));
-        node.next = None;
-        let node_ptr = Some(unsafe { NonNull::new_unchecked(Box::into_raw(node)) });
+        self.push_back(obj);
+    }
+
+    pub fn push_back(&mut self, obj: T) {
+        let node_ptr = Some(Self::alloc(obj));
         match self.end {
             None => self.start = node_ptr,
             Some(end_ptr) => unsafe { (*end_ptr.as_ptr()).next = node_ptr },
@@ -52,62 +73,346 @@ impl<T> LinkedList<T> {
         self.length += 1;
     }
 
+    pub fn push_front(&mut self, obj: T) {
+        let node_ptr = Self::alloc(obj);
+        unsafe { (*node_ptr.as_ptr()).next = self.start };
+        if self.end.is_none() {
+            self.end = Some(node_ptr);
+        }
+        self.start = Some(node_ptr);
+        self.length += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.start.map(|start_ptr| {
+            // 取回所有权，节点在此处释放
+            let node = unsafe { Box::from_raw(start_ptr.as_ptr()) };
+            self.start = node.next;
+            if self.start.is_none() {
+                self.end = None;
+            }
+            self.length -= 1;
+            node.val
+        })
+    }
+
+    // 单链表没有前驱指针，需要从头找到倒数第二个节点，时间复杂度 O(n)
+    pub fn pop_back(&mut self) -> Option<T> {
+        match self.length {
+            0 => None,
+            1 => self.pop_front(),
+            n => self.remove(n as usize - 1),
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.start.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.start.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.end.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.end.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
     pub fn get(&self, index: i32) -> Option<&T> {
         self.get_ith_node(self.start, index)
     }
 
+    pub fn get_mut(&mut self, index: i32) -> Option<&mut T> {
+        if index < 0 {
+            return None;
+        }
+        let node = self.node_at(index as usize)?;
+        Some(unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    // 在 index 处插入，index 等于长度时追加到尾部；index 超过长度时 panic
+    pub fn insert(&mut self, index: usize, obj: T) {
+        assert!(
+            index <= self.len(),
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            self.len()
+        );
+        if index == 0 {
+            return self.push_front(obj);
+        }
+        if index == self.len() {
+            return self.push_back(obj);
+        }
+        let prev = self.node_at(index - 1).unwrap();
+        let node_ptr = Self::alloc(obj);
+        unsafe {
+            (*node_ptr.as_ptr()).next = (*prev.as_ptr()).next;
+            (*prev.as_ptr()).next = Some(node_ptr);
+        }
+        self.length += 1;
+    }
+
+    // 删除并返回 index 处的元素，越界时返回 None
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        if index == 0 {
+            return self.pop_front();
+        }
+        let prev = self.node_at(index - 1)?;
+        unsafe {
+            let node = Box::from_raw((*prev.as_ptr()).next?.as_ptr());
+            (*prev.as_ptr()).next = node.next;
+            if node.next.is_none() {
+                self.end = Some(prev);
+            }
+            self.length -= 1;
+            Some(node.val)
+        }
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.start,
+            remaining: self.len(),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.start,
+            remaining: self.len(),
+            marker: PhantomData,
+        }
+    }
+
+    fn alloc(obj: T) -> NonNull<Node<T>> {
+        // Box::into_raw 的返回值不会为空
+        unsafe { NonNull::new_unchecked(Box::into_raw(Box::new(Node::new(obj)))) }
+    }
+
+    fn node_at(&self, index: usize) -> Option<NonNull<Node<T>>> {
+        let mut node = self.start;
+        for _ in 0..index {
+            node = unsafe { (*node?.as_ptr()).next };
+        }
+        node
+    }
+
     fn get_ith_node(&self, node: Option<NonNull<Node<T>>>, index: i32) -> Option<&T> {
         match node {
             None => None,
             Some(next_ptr) => match index {
                 0 => Some(unsafe { &(*next_ptr.as_ptr()).val }),
+                _ if index < 0 => None,
                 _ => self.get_ith_node(unsafe { (*next_ptr.as_ptr()).next }, index - 1),
             },
         }
     }
 }
 
-impl<T: PartialOrd + Copy> LinkedList<T> {
-    pub fn merge(list_a: LinkedList<T>, list_b: LinkedList<T>) -> Self {
-        let mut list_c = Self {
-            length: 0,
-            start: None,
-            end: None,
-        };
+impl<T: PartialOrd> LinkedList<T> {
+    // 合并两个有序链表：直接重新链接原有节点，不复制元素也不重新分配内存
+    pub fn merge(mut list_a: LinkedList<T>, mut list_b: LinkedList<T>) -> Self {
+        let mut list_c = Self::new();
+        list_c.length = list_a.length + list_b.length;
+
+        // 节点的所有权转移到 list_c，list_a 和 list_b 变为空链表，释放时不再重复释放节点
+        let mut node_a = list_a.start.take();
+        let mut node_b = list_b.start.take();
+        list_a.end = None;
+        list_b.end = None;
+        list_a.length = 0;
+        list_b.length = 0;
 
-        let mut node_a = list_a.start;
-        let mut node_b = list_b.start;
-        // 依次比较a,b中的元素,将较小的元素添加到新的单链表中
+        // 依次比较a,b中的元素,将较小的节点接到新的单链表末尾
         loop {
-            match (node_a, node_b) {
+            let next = match (node_a, node_b) {
                 (None, None) => break,
                 (None, Some(b)) => {
-                    list_c.add(unsafe { (*b.as_ptr()).val });
                     node_b = unsafe { (*b.as_ptr()).next };
+                    b
                 }
                 (Some(a), None) => {
-                    list_c.add(unsafe { (*a.as_ptr()).val });
                     node_a = unsafe { (*a.as_ptr()).next };
+                    a
                 }
                 (Some(a), Some(b)) => {
-                    let val_a = unsafe { (*a.as_ptr()).val };
-                    let val_b = unsafe { (*b.as_ptr()).val };
+                    let val_a = unsafe { &(*a.as_ptr()).val };
+                    let val_b = unsafe { &(*b.as_ptr()).val };
 
                     if val_a < val_b {
-                        list_c.add(val_a);
                         node_a = unsafe { (*a.as_ptr()).next };
+                        a
                     } else {
-                        list_c.add(val_b);
                         node_b = unsafe { (*b.as_ptr()).next };
+                        b
                     }
                 }
+            };
+            unsafe { (*next.as_ptr()).next = None };
+            match list_c.end {
+                None => list_c.start = Some(next),
+                Some(end_ptr) => unsafe { (*end_ptr.as_ptr()).next = Some(next) },
             }
+            list_c.end = Some(next);
         }
 
         list_c
     }
 }
 
+// 逐个释放节点；不使用递归，长链表也不会栈溢出
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for LinkedList<T> {}
+
+impl<T: Debug> Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+// 按顺序借用各元素
+pub struct Iter<'a, T> {
+    next: Option<NonNull<Node<T>>>,
+    remaining: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.next = node.next;
+            self.remaining -= 1;
+            &node.val
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+// 按顺序可变借用各元素，每个节点只返回一次，不会产生重叠的可变引用
+pub struct IterMut<'a, T> {
+    next: Option<NonNull<Node<T>>>,
+    remaining: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.next.map(|node| unsafe {
+            let node = &mut *node.as_ptr();
+            self.next = node.next;
+            self.remaining -= 1;
+            &mut node.val
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+// 按顺序取出各元素，未取出的元素随迭代器一起释放
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len(), Some(self.list.len()))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
 impl<T> Display for LinkedList<T>
 where
     T: Display,
@@ -135,6 +440,170 @@ where
 #[cfg(test)]
 mod tests {
     use super::LinkedList;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // 释放时记录自己的编号，用于检查每个元素恰好释放一次
+    #[derive(Debug, Clone, PartialEq, PartialOrd)]
+    struct Tracked {
+        id: u32,
+        drops: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.drops.borrow_mut().push(self.id);
+        }
+    }
+
+    fn tracked(
+        ids: impl IntoIterator<Item = u32>,
+        drops: &Rc<RefCell<Vec<u32>>>,
+    ) -> LinkedList<Tracked> {
+        ids.into_iter()
+            .map(|id| Tracked {
+                id,
+                drops: Rc::clone(drops),
+            })
+            .collect()
+    }
+
+    fn assert_dropped_once(drops: &Rc<RefCell<Vec<u32>>>, expected: impl IntoIterator<Item = u32>) {
+        let mut dropped = drops.borrow().clone();
+        dropped.sort_unstable();
+        let mut expected: Vec<u32> = expected.into_iter().collect();
+        expected.sort_unstable();
+        assert_eq!(dropped, expected);
+    }
+
+    #[test]
+    fn push_and_pop_at_both_ends() {
+        let mut list = LinkedList::new();
+        assert!(list.is_empty());
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.front(), Some(&1));
+        assert_eq!(list.back(), Some(&3));
+
+        *list.front_mut().unwrap() = 10;
+        *list.back_mut().unwrap() = 30;
+        assert_eq!(list.pop_back(), Some(30));
+        assert_eq!(list.back(), Some(&2));
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.pop_back(), Some(2));
+        assert!(list.is_empty());
+        assert_eq!(list.front(), None);
+        assert_eq!(list.back(), None);
+
+        // 清空后尾指针已重置，可以继续使用
+        list.push_back(4);
+        list.push_front(5);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [5, 4]);
+    }
+
+    #[test]
+    fn insert_and_remove_at_index() {
+        let mut list: LinkedList<i32> = vec![1, 3].into_iter().collect();
+        list.insert(1, 2);
+        list.insert(0, 0);
+        list.insert(4, 4);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+        assert_eq!(list.get(4), Some(&4));
+        assert_eq!(list.get(5), None);
+        assert_eq!(list.get(-1), None);
+        *list.get_mut(2).unwrap() = 20;
+
+        assert_eq!(list.remove(5), None);
+        assert_eq!(list.remove(4), Some(4));
+        assert_eq!(list.back(), Some(&3));
+        assert_eq!(list.remove(0), Some(0));
+        assert_eq!(list.remove(1), Some(20));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 3]);
+        list.push_back(5);
+        assert_eq!(list.back(), Some(&5));
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn iterates_by_reference_and_by_value() {
+        let mut list: LinkedList<i32> = (1..=4).collect();
+        assert_eq!(list.iter().len(), 4);
+        for value in list.iter_mut() {
+            *value *= 10;
+        }
+        for value in &mut list {
+            *value += 1;
+        }
+        assert_eq!((&list).into_iter().sum::<i32>(), 104);
+
+        let mut values = list.into_iter();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values.next(), Some(11));
+        assert_eq!(values.collect::<Vec<_>>(), [21, 31, 41]);
+    }
+
+    #[test]
+    fn clone_compare_and_extend() {
+        let mut list: LinkedList<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+        let copy = list.clone();
+        assert_eq!(list, copy);
+
+        list.extend(vec!["c".to_string()]);
+        assert_ne!(list, copy);
+        assert_eq!(list.len(), 3);
+        assert_eq!(copy.len(), 2);
+        assert_eq!(format!("{:?}", list), r#"["a", "b", "c"]"#);
+        assert_eq!(list.to_string(), "a, b, c");
+
+        list.clear();
+        assert_eq!(list, LinkedList::new());
+    }
+
+    #[test]
+    fn drops_every_element_exactly_once() {
+        let drops = Rc::new(RefCell::new(Vec::new()));
+        {
+            let mut list = tracked(0..6, &drops);
+            list.push_front(tracked([6], &drops).pop_front().unwrap());
+            drop(list.pop_front());
+            drop(list.pop_back());
+            drop(list.remove(1));
+            list.insert(1, tracked([7], &drops).pop_back().unwrap());
+            assert_dropped_once(&drops, [6, 5, 1]);
+
+            let copy = list.clone();
+            let mut values = copy.into_iter();
+            drop(values.next());
+            drop(values);
+            // 克隆的元素与原元素编号相同
+            assert_dropped_once(&drops, [6, 5, 1, 0, 7, 2, 3, 4]);
+        }
+        assert_dropped_once(&drops, [6, 5, 1, 0, 7, 2, 3, 4, 0, 7, 2, 3, 4]);
+    }
+
+    #[test]
+    fn merge_moves_nodes_without_dropping() {
+        let drops = Rc::new(RefCell::new(Vec::new()));
+        let merged = LinkedList::merge(tracked([1, 3, 5], &drops), tracked([2, 4], &drops));
+        assert!(drops.borrow().is_empty());
+        assert_eq!(merged.iter().map(|t| t.id).collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged.back().map(|t| t.id), Some(5));
+        drop(merged);
+        assert_dropped_once(&drops, 1..=5);
+    }
+
+    #[test]
+    fn drops_long_list_without_recursion() {
+        let list: LinkedList<u32> = (0..200_000).collect();
+        assert_eq!(list.len(), 200_000);
+        drop(list);
+    }
 
     #[test]
     fn create_numeric_list() {
@@ -0,0 +1,482 @@
+use crate::config::{Exercise, ExerciseConfig};
+use crate::constraints::is_test;
+use crate::evaluator::Evaluator;
+use crate::files::{copy_file, relative_files, resolve};
+use crate::hint::HINT_USAGE_FILE;
+use crate::report::{ExerciseResult, Report};
+use crate::similarity::csv_field;
+use proc_macro2::LineColumn;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
+use syn::visit::{self, Visit};
+
+// 批量评测的工作目录，每名学生一个子目录，相对于仓库根目录
+const BATCH_DIR: &str = ".cargotest/batch";
+
+// 测试代码常用的标准库宏；可信测试中改用 ::std:: 路径调用，学生代码不得以这些名字定义或导入宏
+const STD_MACROS: [&str; 20] = [
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+    "panic",
+    "unreachable",
+    "todo",
+    "unimplemented",
+    "matches",
+    "vec",
+    "format",
+    "print",
+    "println",
+    "eprint",
+    "eprintln",
+    "write",
+    "writeln",
+    "dbg",
+];
+
+// 配置中没有 timeout_secs 时每道题评测时间的上限（秒），避免死循环的提交拖住整个批次
+pub const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+// 整个班级的成绩册
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Gradebook {
+    // 习题名称，与每名学生的 exercises 顺序一致
+    pub exercises: Vec<String>,
+    pub students: Vec<StudentGrade>,
+}
+
+// 一名学生的成绩
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StudentGrade {
+    pub student: String,
+    pub total_score: i32,
+    pub passed: usize,
+    pub failed: usize,
+    pub exercises: Vec<ExerciseGrade>,
+    // 评测本身出错（如无法准备工作目录）时的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExerciseGrade {
+    pub name: String,
+    pub passed: bool,
+    pub score: i32,
+    // 未通过的简要原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Gradebook {
+    // 每名学生一行，每道习题一列，最后为总分、通过数和未通过原因
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["student".to_string()];
+        header.extend(self.exercises.iter().map(|name| csv_field(name)));
+        header.extend(["total", "passed", "errors"].map(String::from));
+        let mut csv = header.join(",") + "\n";
+
+        for student in &self.students {
+            let mut row = vec![csv_field(&student.student)];
+            row.extend(student.exercises.iter().map(|e| e.score.to_string()));
+            row.push(student.total_score.to_string());
+            row.push(student.passed.to_string());
+            let mut errors: Vec<String> = student.error.iter().cloned().collect();
+            errors.extend(
+                student
+                    .exercises
+                    .iter()
+                    .filter_map(|e| Some(format!("{}: {}", e.name, e.error.as_ref()?))),
+            );
+            row.push(csv_field(&errors.join("; ")));
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+// dir 下包含 exercises 目录的子目录，每个视为一名学生的仓库
+pub fn students(dir: &Path) -> io::Result<Vec<String>> {
+    let mut students: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("exercises").is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    students.sort();
+    Ok(students)
+}
+
+// 用 jobs 个线程并行评测 dir 下每名学生的仓库，每完成一名学生调用一次 on_graded
+// 每名学生在 .cargotest/batch/<学生> 下的独立工作目录中评测，测试代码、配置和对照实现均取自 root
+pub fn grade_batch(
+    root: &Path,
+    dir: &Path,
+    config: &ExerciseConfig,
+    jobs: usize,
+    mut on_graded: impl FnMut(&StudentGrade),
+) -> io::Result<Gradebook> {
+    let students = students(dir)?;
+    let exercises = config.all_exercises();
+    let next = AtomicUsize::new(0);
+    let grades = Mutex::new(Vec::new());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, students.len().max(1)) {
+            let sender = sender.clone();
+            let (students, exercises, next, grades) = (&students, &exercises, &next, &grades);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(student) = students.get(index) else {
+                    break;
+                };
+                let grade = grade_student(root, &dir.join(student), student, config, exercises);
+                let _ = sender.send(grade.clone());
+                grades.lock().unwrap().push(grade);
+            });
+        }
+        drop(sender);
+        for grade in receiver {
+            on_graded(&grade);
+        }
+    });
+
+    let mut students = grades.into_inner().unwrap();
+    students.sort_by(|a, b| a.student.cmp(&b.student));
+    Ok(Gradebook {
+        exercises: exercises.into_iter().map(|e| e.name).collect(),
+        students,
+    })
+}
+
+fn grade_student(
+    root: &Path,
+    checkout: &Path,
+    student: &str,
+    config: &ExerciseConfig,
+    exercises: &[Exercise],
+) -> StudentGrade {
+    let workspace = root.join(BATCH_DIR).join(student);
+    let report = prepare_workspace(root, checkout, &workspace, config, exercises)
+        .map_err(|e| format!("could not prepare workspace: {}", e))
+        .and_then(|_| {
+            // 在学生的工作目录中检查，与实际评测使用同一工具链
+            let mut evaluator = Evaluator::new(&workspace, config)
+                .default_timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+            evaluator
+                .check_toolchain()
+                .map_err(|e| format!("refusing to grade: {}", e))?;
+            let report = evaluator.evaluate_all(config);
+            // 保存每名学生的完整报告，便于事后查看
+            let _ = report.save(workspace.join("report.json"));
+            Ok(report)
+        });
+
+    match report {
+        Ok(report) => student_grade(student, &workspace, &report),
+        Err(error) => StudentGrade {
+            student: student.to_string(),
+            total_score: 0,
+            passed: 0,
+            failed: exercises.len(),
+            exercises: Vec::new(),
+            error: Some(error),
+        },
+    }
+}
+
+fn student_grade(student: &str, workspace: &Path, report: &Report) -> StudentGrade {
+    StudentGrade {
+        student: student.to_string(),
+        total_score: report.statistics.total_score,
+        passed: report.statistics.total_successes,
+        failed: report.statistics.total_failures,
+        exercises: report
+            .exercises
+            .iter()
+            .map(|result| ExerciseGrade {
+                name: result.name.clone(),
+                passed: result.result,
+                score: result.score,
+                error: (!result.result).then(|| failure_summary(workspace, result)),
+            })
+            .collect(),
+        error: None,
+    }
+}
+
+// 重建学生的工作目录：习题源码和提示使用记录取自学生仓库，测试代码及习题目录中 src 以外的文件取自 root
+fn prepare_workspace(
+    root: &Path,
+    checkout: &Path,
+    workspace: &Path,
+    config: &ExerciseConfig,
+    exercises: &[Exercise],
+) -> io::Result<()> {
+    if workspace.exists() {
+        fs::remove_dir_all(workspace)?;
+    }
+    fs::create_dir_all(workspace)?;
+
+    for exercise in exercises {
+        let trusted = exercise.source_path(root);
+        let submitted = exercise.source_path(checkout);
+        let target = exercise.source_path(workspace);
+        for rel in relative_files(&trusted)? {
+            let is_source = rel.as_os_str().is_empty() || rel.starts_with("src");
+            let from = resolve(&submitted, &rel);
+            if !is_source {
+                copy_file(&resolve(&trusted, &rel), &resolve(&target, &rel))?;
+            } else if !from.exists() {
+                // 学生没有提交的源文件不用可信版本代替，评测会因缺少文件而失败
+                continue;
+            } else if rel.extension().is_some_and(|ext| ext == "rs") || rel.as_os_str().is_empty() {
+                let merged = with_trusted_tests(
+                    &fs::read_to_string(&from)?,
+                    &fs::read_to_string(resolve(&trusted, &rel))?,
+                );
+                write_file(&resolve(&target, &rel), &merged)?;
+            } else {
+                copy_file(&from, &resolve(&target, &rel))?;
+            }
+        }
+        // 学生新增的源文件（如拆分出的模块）
+        for rel in relative_files(&submitted)? {
+            let to = resolve(&target, &rel);
+            if !rel.starts_with("src") || to.exists() {
+                continue;
+            }
+            let from = resolve(&submitted, &rel);
+            if rel.extension().is_some_and(|ext| ext == "rs") {
+                let source = fs::read_to_string(&from)?;
+                write_file(&to, &reject_std_macros(&source).unwrap_or(source))?;
+            } else {
+                copy_file(&from, &to)?;
+            }
+        }
+    }
+
+    for path in trusted_files(config) {
+        let from = root.join(&path);
+        if from.is_file() {
+            copy_file(&from, &workspace.join(&path))?;
+        }
+    }
+    // 学生查看过的提示层数，用于按 hint_penalty 扣分
+    let hint_usage = checkout.join(HINT_USAGE_FILE);
+    if hint_usage.is_file() {
+        copy_file(&hint_usage, &workspace.join(HINT_USAGE_FILE))?;
+    }
+    Ok(())
+}
+
+// 配置中引用的对照实现、输入生成器和评测脚本
+fn trusted_files(config: &ExerciseConfig) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for exercise in config.all_exercises() {
+        if let Some(settings) = &exercise.differential {
+            paths.push(PathBuf::from(&settings.harness));
+        }
+        if let Some(settings) = &exercise.complexity {
+            paths.push(PathBuf::from(&settings.harness));
+        }
+    }
+    for script in config.custom_types.values() {
+        paths.push(PathBuf::from(&script.command));
+        paths.extend(script.args.iter().map(PathBuf::from));
+    }
+    paths.retain(|path| path.is_relative());
+    paths
+}
+
+// 去掉学生代码中的测试，换成可信版本中的测试；学生代码无法解析时原样保留，编译会失败。
+// 可信测试中的标准库宏改用 ::std:: 路径，学生代码重新定义这些宏时整个文件换成编译错误
+fn with_trusted_tests(submitted: &str, trusted: &str) -> String {
+    if let Some(rejected) = reject_std_macros(submitted) {
+        return rejected;
+    }
+    let (Ok(submitted_file), Ok(trusted_file)) =
+        (syn::parse_file(submitted), syn::parse_file(trusted))
+    else {
+        return submitted.to_string();
+    };
+
+    let mut merged = String::new();
+    let mut copied = 0;
+    for item in submitted_file
+        .items
+        .iter()
+        .filter(|item| is_test_item(item))
+    {
+        let (start, end) = item_range(submitted, item);
+        merged.push_str(&submitted[copied..start]);
+        copied = end;
+    }
+    merged.push_str(&submitted[copied..]);
+
+    for item in trusted_file.items.iter().filter(|item| is_test_item(item)) {
+        let (start, end) = item_range(trusted, item);
+        let mut macros = StdMacros::default();
+        macros.visit_item(item);
+        let mut text = trusted[start..end].to_string();
+        for position in macros.invocations.iter().rev() {
+            text.insert_str(offset(trusted, *position) - start, "::std::");
+        }
+        merged.push('\n');
+        merged.push_str(&text);
+        merged.push('\n');
+    }
+    merged
+}
+
+// 学生代码以标准库宏的名字定义或导入宏时（例如把 assert_eq 定义为空宏，使可信测试失效），
+// 返回代替该文件的编译错误
+fn reject_std_macros(source: &str) -> Option<String> {
+    let file = syn::parse_file(source).ok()?;
+    let mut macros = StdMacros::default();
+    macros.visit_file(&file);
+    let name = macros.redefined?;
+    Some(format!(
+        "compile_error!(\"the submission redefines the standard macro `{}!`, which the trusted tests rely on\");\n",
+        name
+    ))
+}
+
+// 标准库宏的调用位置，以及第一个以标准库宏的名字定义或导入的宏
+#[derive(Default)]
+struct StdMacros {
+    invocations: Vec<LineColumn>,
+    redefined: Option<String>,
+}
+
+impl StdMacros {
+    fn check(&mut self, ident: &syn::Ident) {
+        if self.redefined.is_none() && STD_MACROS.contains(&ident.to_string().as_str()) {
+            self.redefined = Some(ident.to_string());
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for StdMacros {
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Some(ident) = mac.path.get_ident() {
+            if STD_MACROS.contains(&ident.to_string().as_str()) {
+                self.invocations.push(ident.span().start());
+            }
+        }
+        visit::visit_macro(self, mac);
+    }
+
+    fn visit_item_macro(&mut self, item: &'ast syn::ItemMacro) {
+        if let Some(ident) = &item.ident {
+            self.check(ident);
+        }
+        visit::visit_item_macro(self, item);
+    }
+
+    fn visit_use_rename(&mut self, rename: &'ast syn::UseRename) {
+        self.check(&rename.rename);
+        visit::visit_use_rename(self, rename);
+    }
+}
+
+fn is_test_item(item: &syn::Item) -> bool {
+    match item {
+        syn::Item::Fn(function) => is_test(&function.attrs),
+        syn::Item::Mod(module) => is_test(&module.attrs),
+        _ => false,
+    }
+}
+
+// 顶层条目（含属性）在源码中的字节范围
+fn item_range(text: &str, item: &syn::Item) -> (usize, usize) {
+    use quote::ToTokens;
+    let mut tokens = item.to_token_stream().into_iter();
+    let first = tokens.next().map(|t| t.span().start());
+    let last = tokens.last().map(|t| t.span().end()).or(first);
+    match (first, last) {
+        (Some(start), Some(end)) => (offset(text, start), offset(text, end)),
+        _ => (0, 0),
+    }
+}
+
+// 行号从 1 开始、列号按字符计数的位置在源码中的字节偏移
+fn offset(text: &str, position: LineColumn) -> usize {
+    let line_start: usize = text
+        .split_inclusive('\n')
+        .take(position.line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let line = &text[line_start..];
+    line_start
+        + line
+            .char_indices()
+            .nth(position.column)
+            .map_or(line.len(), |(i, _)| i)
+}
+
+// 未通过的原因：静态约束、反例、泄漏、复杂度，或失败步骤输出中的第一条错误
+fn failure_summary(workspace: &Path, result: &ExerciseResult) -> String {
+    if let Some(violation) = result.sandbox_violations.first() {
+        return format!("sandbox violation in {}", violation);
+    }
+    if let Some(v) = result.violations.first() {
+        return format!("{}:{}: {}", v.file, v.line, v.message);
+    }
+    if let Some(c) = &result.counterexample {
+        return format!(
+            "counterexample {}: expected {}, got {}",
+            c.input, c.expected, c.actual
+        );
+    }
+    if let Some(leak) = result.leaks.first() {
+        return format!("{} leaked {} allocation(s)", leak.test, leak.allocations);
+    }
+    if let Some(c) = result.complexity.as_ref().filter(|c| !c.within_target()) {
+        return format!("estimated {} exceeds target {}", c.estimated, c.target);
+    }
+    if result.timed_out {
+        return "timed out".to_string();
+    }
+    if result.cpu_budget_exceeded {
+        return format!("CPU time {} ms over budget", result.cpu_ms.unwrap_or(0));
+    }
+    let Some(step) = result.logs.iter().rev().find(|step| !step.success) else {
+        return "not evaluated".to_string();
+    };
+    let output = [&step.stderr, &step.stdout]
+        .iter()
+        .map(|path| fs::read_to_string(workspace.join(path)).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+    // panic 信息在 "panicked at <位置>:" 的下一行
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+    let detail = lines
+        .iter()
+        .position(|line| line.starts_with("error") || line.contains("panicked at"))
+        .map(|i| match lines.get(i + 1) {
+            Some(next) if lines[i].ends_with(':') && !next.is_empty() => {
+                format!(": {} {}", lines[i], next)
+            }
+            _ => format!(": {}", lines[i]),
+        })
+        .unwrap_or_default();
+    format!("{} failed{}", step.step, detail)
+}
+
+fn write_file(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)
+}
@@ -0,0 +1,347 @@
+use crate::instrument;
+use crate::sandbox::{self, Sandbox};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// 驱动程序输出测量结果所用的标记，libtest 可能在同一行之前输出测试名
+const REPORT_MARKER: &str = "__CARGOTEST_COMPLEXITY__ ";
+
+const DRIVER_TEST: &str = "__cargotest_complexity::run";
+
+// 相邻两个规模间耗时增长的指数超过此值时视为指数级
+const EXPONENTIAL_SLOPE: f64 = 4.0;
+
+// 追加到习题源码末尾的驱动程序，{harness} 替换为输入生成器文件的路径，{rng} 替换为随机数生成器。
+// 输入生成器的 expected 给出正确结果时先核对解答的结果
+const DRIVER: &str = r#"
+mod __cargotest_complexity {
+    #[path = {harness}]
+    mod harness;
+
+    {rng}
+
+    use std::time::Instant;
+
+    // 批量运行 iterations 次，返回平均每次的耗时（纳秒）
+    fn measure(input: &harness::Input, iterations: u64) -> f64 {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            std::hint::black_box(harness::run(std::hint::black_box(input)));
+        }
+        start.elapsed().as_nanos() as f64 / iterations as f64
+    }
+
+    #[test]
+    fn run() {
+        let sizes: Vec<u64> = std::env::var("CARGOTEST_SIZES")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|s| s.parse().ok())
+            .collect();
+
+        let mut rng = Rng::new(0);
+        for size in sizes {
+            let input = harness::generate(size, &mut rng);
+            // 先核对结果，错误的解法不计时
+            if let Some(expected) = harness::expected(&input) {
+                let actual = harness::run(&input);
+                if actual != expected {
+                    println!(
+                        "__CARGOTEST_COMPLEXITY__ wrong {} expected {:?}, actual {:?}",
+                        size, expected, actual
+                    );
+                    return;
+                }
+            }
+            // 每批至少运行 2 毫秒以减小计时误差，取 5 批中最快的一批
+            let mut iterations = 1;
+            while measure(&input, iterations) * (iterations as f64) < 2_000_000.0 {
+                iterations *= 2;
+            }
+            let nanos = (0..5)
+                .map(|_| measure(&input, iterations))
+                .fold(f64::INFINITY, f64::min);
+            println!("__CARGOTEST_COMPLEXITY__ {} {}", size, nanos);
+        }
+    }
+}
+"#;
+
+// 时间复杂度的类别，按增长速度从慢到快排列
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Complexity {
+    #[serde(rename = "1")]
+    Constant,
+    #[serde(rename = "log n")]
+    Logarithmic,
+    #[serde(rename = "n")]
+    Linear,
+    #[serde(rename = "n log n")]
+    Linearithmic,
+    #[serde(rename = "n^2")]
+    Quadratic,
+    #[serde(rename = "n^3")]
+    Cubic,
+    #[serde(rename = "2^n")]
+    Exponential,
+}
+
+impl Complexity {
+    // 参与拟合的多项式类别，指数级由相邻规模的增长速度单独判断
+    const POLYNOMIAL: [Complexity; 6] = [
+        Complexity::Constant,
+        Complexity::Logarithmic,
+        Complexity::Linear,
+        Complexity::Linearithmic,
+        Complexity::Quadratic,
+        Complexity::Cubic,
+    ];
+
+    // ln f(n)，用于在对数坐标下拟合 t = c * f(n)
+    fn ln_growth(self, n: f64) -> f64 {
+        let ln_n = n.max(2.0).ln();
+        match self {
+            Complexity::Constant => 0.0,
+            Complexity::Logarithmic => ln_n.ln(),
+            Complexity::Linear => ln_n,
+            Complexity::Linearithmic => ln_n + ln_n.ln(),
+            Complexity::Quadratic => 2.0 * ln_n,
+            Complexity::Cubic => 3.0 * ln_n,
+            Complexity::Exponential => n * std::f64::consts::LN_2,
+        }
+    }
+}
+
+impl fmt::Display for Complexity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Complexity::Constant => "1",
+            Complexity::Logarithmic => "log n",
+            Complexity::Linear => "n",
+            Complexity::Linearithmic => "n log n",
+            Complexity::Quadratic => "n^2",
+            Complexity::Cubic => "n^3",
+            Complexity::Exponential => "2^n",
+        };
+        write!(f, "O({})", name)
+    }
+}
+
+// 复杂度检查配置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ComplexitySettings {
+    // 输入生成器文件，相对于仓库根目录
+    pub harness: String,
+    // 题目要求的复杂度
+    pub target: Complexity,
+    // 输入规模从 start 开始，每次乘以 factor，共测量 steps 个规模
+    #[serde(default = "default_start")]
+    pub start: u64,
+    #[serde(default = "default_factor")]
+    pub factor: u64,
+    #[serde(default = "default_steps")]
+    pub steps: u32,
+    // 单个规模的测量超过此时间即停止，剩余规模不再测量
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    // 估计的复杂度超过目标时判为不通过，否则只给出警告
+    #[serde(default)]
+    pub fail: bool,
+}
+
+fn default_start() -> u64 {
+    1000
+}
+
+fn default_factor() -> u64 {
+    2
+}
+
+fn default_steps() -> u32 {
+    6
+}
+
+fn default_timeout_ms() -> u64 {
+    2000
+}
+
+impl ComplexitySettings {
+    // 按几何级数增长的输入规模
+    pub fn sizes(&self) -> Vec<u64> {
+        std::iter::successors(Some(self.start.max(1)), |size| {
+            size.checked_mul(self.factor.max(2))
+        })
+        .take(self.steps as usize)
+        .collect()
+    }
+}
+
+// 某一输入规模下单次运行的耗时
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sample {
+    pub size: u64,
+    pub nanos: f64,
+}
+
+// 复杂度检查的结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ComplexityEstimate {
+    pub target: Complexity,
+    pub estimated: Complexity,
+    pub samples: Vec<Sample>,
+    // 测量超时的输入规模
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timed_out: Option<u64>,
+}
+
+impl ComplexityEstimate {
+    pub fn within_target(&self) -> bool {
+        self.estimated <= self.target
+    }
+}
+
+// 在逐渐增大的输入上测量单文件习题的耗时并估计复杂度，测量点不足两个时返回 None
+pub fn check_single_file(
+    root: &Path,
+    file_path: &Path,
+    settings: &ComplexitySettings,
+    sandbox: Option<&Sandbox>,
+) -> io::Result<Option<ComplexityEstimate>> {
+    let harness = fs::canonicalize(root.join(&settings.harness))?;
+    let driver = DRIVER
+        .replace("{rng}", instrument::RNG)
+        .replace("{harness}", &format!("{:?}", harness.display().to_string()));
+    let instrumented = instrument::build_single_file(
+        root,
+        file_path,
+        &driver,
+        "complexity",
+        std::slice::from_ref(&harness),
+        sandbox,
+    )?;
+
+    let sizes = settings.sizes();
+    let size_list: Vec<String> = sizes.iter().map(u64::to_string).collect();
+    let mut command = Command::new(&instrumented.binary);
+    command
+        .args(["--exact", DRIVER_TEST, "--nocapture", "--test-threads=1"])
+        .env("CARGOTEST_SIZES", size_list.join(","))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    if let Some(sandbox) = sandbox {
+        sandbox.apply(&mut command)?;
+    }
+    let mut child = command.spawn()?;
+
+    // 在单独的线程中读取输出，主线程按规模逐个等待，超时即终止
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if let Some(sample) = parse_line(&line) {
+                if sender.send(sample).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    let timeout = Duration::from_millis(settings.timeout_ms);
+    let mut samples = Vec::new();
+    let mut timed_out = None;
+    let mut wrong = None;
+    for &size in &sizes {
+        match receiver.recv_timeout(timeout) {
+            Ok(Ok(sample)) => samples.push(sample),
+            Ok(Err(message)) => {
+                wrong = Some(message);
+                break;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                timed_out = Some(size);
+                break;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    let _ = child.kill();
+    let status = child.wait()?;
+    if let Some(violation) = sandbox::violation(&status) {
+        return Err(io::Error::other(format!("sandbox violation: {}", violation)));
+    }
+    if let Some(message) = wrong {
+        return Err(io::Error::other(message));
+    }
+    if timed_out.is_none() && samples.len() < sizes.len() && !status.success() {
+        return Err(io::Error::other("complexity driver failed"));
+    }
+
+    // 超时的规模以超时时间作为耗时的下界参与估计
+    let mut measured = samples.clone();
+    if let Some(size) = timed_out {
+        measured.push(Sample {
+            size,
+            nanos: timeout.as_nanos() as f64,
+        });
+    }
+    Ok(estimate(&measured).map(|estimated| ComplexityEstimate {
+        target: settings.target,
+        estimated,
+        samples,
+        timed_out,
+    }))
+}
+
+// 解析驱动程序输出的一行：测量结果，或某一规模下的错误结果
+fn parse_line(line: &str) -> Option<Result<Sample, String>> {
+    let (_, rest) = line.split_once(REPORT_MARKER)?;
+    if let Some(wrong) = rest.strip_prefix("wrong ") {
+        let (size, values) = wrong.split_once(' ')?;
+        return Some(Err(format!("wrong result at size {}: {}", size, values.trim())));
+    }
+    let (size, nanos) = rest.trim().split_once(' ')?;
+    Some(Ok(Sample {
+        size: size.parse().ok()?,
+        nanos: nanos.parse().ok()?,
+    }))
+}
+
+// 在对数坐标下用 t = c * f(n) 拟合各多项式类别，取残差最小者；
+// 最后两个规模之间的增长指数超过 EXPONENTIAL_SLOPE 时判为指数级
+pub fn estimate(samples: &[Sample]) -> Option<Complexity> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|s| (s.size as f64, s.nanos.max(1.0).ln()))
+        .collect();
+
+    let (n1, t1) = points[points.len() - 2];
+    let (n2, t2) = points[points.len() - 1];
+    if (t2 - t1) / (n2.ln() - n1.ln()) > EXPONENTIAL_SLOPE {
+        return Some(Complexity::Exponential);
+    }
+
+    Complexity::POLYNOMIAL
+        .iter()
+        .map(|&class| {
+            // 固定斜率时截距的最小二乘解为残差的均值
+            let residuals: Vec<f64> = points
+                .iter()
+                .map(|&(n, t)| t - class.ln_growth(n))
+                .collect();
+            let mean = residuals.iter().sum::<f64>() / residuals.len() as f64;
+            let error: f64 = residuals.iter().map(|r| (r - mean).powi(2)).sum();
+            (class, error)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(class, _)| class)
+}
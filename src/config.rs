@@ -0,0 +1,327 @@
+use crate::complexity::ComplexitySettings;
+use crate::constraints::ConstraintSettings;
+use crate::differential::DifferentialSettings;
+use crate::environment::parse_version;
+use crate::io_program::IoSettings;
+use crate::leak::LeakCheck;
+use crate::registry::{EvaluatorRegistry, BUILTIN_TYPES};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Exercise {
+    pub name: String,
+    pub path: String,
+    #[serde(rename = "type")]
+    pub exercise_type: String,
+    pub score: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<String>,
+    #[serde(default)]
+    pub leak_check: LeakCheck,
+    #[serde(default)]
+    pub io: IoSettings,
+    #[serde(default)]
+    pub differential: Option<DifferentialSettings>,
+    // 未通过时的重试次数，覆盖全局的 retries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    // 需要先通过的习题名称
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prerequisites: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraints: Option<ConstraintSettings>,
+    // 在逐渐增大的输入上测量耗时，估计时间复杂度
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complexity: Option<ComplexitySettings>,
+    // 是否在沙箱中运行测试程序，覆盖全局的 sandbox
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<bool>,
+    // 测试程序（或学生程序）可用的 CPU 时间上限（毫秒），超出即判为不通过
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_budget_ms: Option<u64>,
+    // 评测这道题（含编译和全部重试）的时间上限（秒），覆盖全局的 timeout_secs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+impl Exercise {
+    // 习题在仓库中的路径
+    pub fn source_path(&self, root: &Path) -> PathBuf {
+        root.join("exercises").join(&self.path)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExerciseConfig {
+    pub easy: Vec<Exercise>,
+    pub normal: Vec<Exercise>,
+    pub hard: Vec<Exercise>,
+    // 每查看一层提示扣除的分数
+    #[serde(default)]
+    pub hint_penalty: i32,
+    // 未通过的题目最多重试的次数
+    #[serde(default)]
+    pub retries: u32,
+    // 在沙箱中运行测试程序和学生程序，目前仅支持 Linux
+    #[serde(default)]
+    pub sandbox: bool,
+    // 每道题评测时间的上限（秒），超过后终止正在运行的命令并判为不通过；未设置时不限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    // 评测所需的最低 rustc 版本，如 "1.70.0"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_rustc: Option<String>,
+    // 由外部脚本评测的自定义习题类型
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom_types: BTreeMap<String, ScriptSettings>,
+    // 各难度的分值与评分规则，键为 easy / normal / hard
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, CategoryPolicy>,
+}
+
+// 某一难度的评分规则
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CategoryPolicy {
+    // 该难度题目分值之和应等于此值
+    #[serde(default)]
+    pub max_score: Option<i32>,
+    // 该难度最多计入的分数
+    #[serde(default)]
+    pub cap: Option<i32>,
+    // 至少通过的题目数，未达到时该难度记 0 分
+    #[serde(default)]
+    pub min_passed: usize,
+}
+
+// 外部脚本评测的命令，参数中的 {path} 和 {name} 替换为习题路径和名称
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScriptSettings {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "{}", e),
+            ConfigError::Parse(e) => write!(f, "{}", e),
+            ConfigError::Invalid(problems) => write!(f, "{}", problems.join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        ConfigError::Parse(e)
+    }
+}
+
+impl ExerciseConfig {
+    // 读取并校验配置文件，只接受内置类型和 custom_types 中声明的类型
+    pub fn load(file_path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::load_with(file_path, &EvaluatorRegistry::builtin())
+    }
+
+    // 读取配置文件，并接受 registry 中额外注册的类型
+    pub fn load_with(
+        file_path: impl AsRef<Path>,
+        registry: &EvaluatorRegistry,
+    ) -> Result<Self, ConfigError> {
+        let file = File::open(file_path)?;
+        let config: ExerciseConfig = serde_json::from_reader(file)?;
+        config.validate(registry)?;
+        Ok(config)
+    }
+
+    // 检查重名、未知类型和非法分值，一次性返回全部问题
+    pub fn validate(&self, registry: &EvaluatorRegistry) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        let mut names = HashSet::new();
+        let all = self.all_exercises();
+
+        for name in self.custom_types.keys() {
+            if BUILTIN_TYPES.contains(&name.as_str()) {
+                problems.push(format!("custom type {} shadows a built-in type", name));
+            }
+        }
+
+        for exercise in &all {
+            if exercise.name.is_empty() {
+                problems.push(format!("exercise at {} has no name", exercise.path));
+            }
+            if !names.insert(exercise.name.clone()) {
+                problems.push(format!("duplicate exercise name {}", exercise.name));
+            }
+            if !registry.contains(&exercise.exercise_type)
+                && !self.custom_types.contains_key(&exercise.exercise_type)
+            {
+                problems.push(format!(
+                    "{} has unknown type {}",
+                    exercise.name, exercise.exercise_type
+                ));
+            }
+            if exercise.score < 0 {
+                problems.push(format!("{} has a negative score", exercise.name));
+            }
+            for prerequisite in &exercise.prerequisites {
+                if find_exercise(&all, prerequisite).is_none() {
+                    problems.push(format!(
+                        "{} has unknown prerequisite {}",
+                        exercise.name, prerequisite
+                    ));
+                }
+            }
+        }
+        if let Err(cycle) = self.topological_order() {
+            problems.push(format!("prerequisites form a cycle: {}", cycle.join(" -> ")));
+        }
+        for (category, policy) in &self.categories {
+            let exercises = match self
+                .category_exercises()
+                .into_iter()
+                .find(|(name, _)| name == category)
+            {
+                Some((_, exercises)) => exercises,
+                None => {
+                    problems.push(format!("unknown category {}", category));
+                    continue;
+                }
+            };
+            let total: i32 = exercises.iter().map(|e| e.score).sum();
+            if let Some(max_score) = policy.max_score {
+                if total != max_score {
+                    problems.push(format!(
+                        "{} scores add up to {} but max_score is {}",
+                        category, total, max_score
+                    ));
+                }
+            }
+            if policy.min_passed > exercises.len() {
+                problems.push(format!(
+                    "{} requires {} passed exercises but only has {}",
+                    category,
+                    policy.min_passed,
+                    exercises.len()
+                ));
+            }
+        }
+        if let Some(version) = &self.min_rustc {
+            if parse_version(version).is_none() {
+                problems.push(format!("min_rustc {} is not a valid version", version));
+            }
+        }
+        if self.hint_penalty < 0 {
+            problems.push("hint_penalty must not be negative".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    pub fn all_exercises(&self) -> Vec<Exercise> {
+        [self.easy.clone(), self.normal.clone(), self.hard.clone()].concat()
+    }
+
+    // 按先修关系排序的习题，每道题排在其先修题之后，其余保持配置中的顺序
+    // 存在循环依赖时返回构成循环的习题名称
+    pub fn topological_order(&self) -> Result<Vec<Exercise>, Vec<String>> {
+        let all = self.all_exercises();
+        let mut state = HashMap::new();
+        let mut order = Vec::new();
+        let mut stack = Vec::new();
+        for exercise in &all {
+            visit(&all, exercise, &mut state, &mut stack, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    // 尚未通过的先修题名称，为空表示该题已解锁
+    pub fn locked_by(&self, exercise: &Exercise, passed: &HashSet<String>) -> Vec<String> {
+        let all = self.all_exercises();
+        exercise
+            .prerequisites
+            .iter()
+            .filter_map(|name| find_exercise(&all, name))
+            .filter(|prerequisite| !passed.contains(&prerequisite.name))
+            .map(|prerequisite| prerequisite.name.clone())
+            .collect()
+    }
+
+    // 按难度分组的习题
+    pub fn category_exercises(&self) -> [(&'static str, &[Exercise]); 3] {
+        [
+            ("easy", &self.easy),
+            ("normal", &self.normal),
+            ("hard", &self.hard),
+        ]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+// 深度优先遍历先修题，遇到正在访问的习题即存在循环
+fn visit(
+    all: &[Exercise],
+    exercise: &Exercise,
+    state: &mut HashMap<String, Visit>,
+    stack: &mut Vec<String>,
+    order: &mut Vec<Exercise>,
+) -> Result<(), Vec<String>> {
+    match state.get(&exercise.name) {
+        Some(Visit::Done) => return Ok(()),
+        Some(Visit::InProgress) => {
+            let start = stack.iter().position(|name| *name == exercise.name).unwrap_or(0);
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(exercise.name.clone());
+            return Err(cycle);
+        }
+        None => {}
+    }
+
+    state.insert(exercise.name.clone(), Visit::InProgress);
+    stack.push(exercise.name.clone());
+    for name in &exercise.prerequisites {
+        if let Some(prerequisite) = find_exercise(all, name) {
+            visit(all, prerequisite, state, stack, order)?;
+        }
+    }
+    stack.pop();
+    state.insert(exercise.name.clone(), Visit::Done);
+    order.push(exercise.clone());
+    Ok(())
+}
+
+// 按名称查找习题，名称可省略 .rs 后缀
+pub fn find_exercise<'a>(exercises: &'a [Exercise], name: &str) -> Option<&'a Exercise> {
+    exercises
+        .iter()
+        .find(|e| e.name == name || e.name.trim_end_matches(".rs") == name)
+}
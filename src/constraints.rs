@@ -0,0 +1,504 @@
+use crate::config::Exercise;
+use crate::files::{relative_files, resolve};
+use proc_macro2::{Delimiter, Spacing, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Attribute, Block, Expr, ImplItem, Item, Token};
+
+// 习题源码的静态约束，如"不使用 + 运算符"、"不使用额外的哈希表"
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ConstraintSettings {
+    // 只检查这些函数（含 impl 中的方法）及它们直接或间接调用的习题中的函数；为空时检查测试以外的全部代码
+    #[serde(default)]
+    pub functions: Vec<String>,
+    // 禁止的运算符，如 "+"、"+="
+    #[serde(default)]
+    pub forbidden_operators: Vec<String>,
+    // 禁止出现的类型或路径，如 "HashSet"、"Vec::new"；以 ! 结尾的表示宏，如 "vec!"
+    #[serde(default)]
+    pub forbidden_paths: Vec<String>,
+    // 禁止调用的函数或方法名，如 "sum"、"clone"
+    #[serde(default)]
+    pub forbidden_calls: Vec<String>,
+}
+
+// 一处违反约束的代码
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Violation {
+    // 相对于仓库根目录的文件路径
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+// 检查习题源码；Cargo 项目检查 src 目录下的全部 .rs 文件，被检查函数调用的函数可以在其中任一文件中
+pub fn check_exercise(
+    root: &Path,
+    exercise: &Exercise,
+    settings: &ConstraintSettings,
+) -> io::Result<Vec<Violation>> {
+    let source = exercise.source_path(root);
+    let display_root = Path::new("exercises").join(&exercise.path);
+    let mut files = Vec::new();
+    for rel in relative_files(&source)? {
+        let is_source = rel.as_os_str().is_empty()
+            || (rel.starts_with("src") && rel.extension().is_some_and(|ext| ext == "rs"));
+        if !is_source {
+            continue;
+        }
+        let display = resolve(&display_root, &rel).to_string_lossy().replace('\\', "/");
+        let text = fs::read_to_string(resolve(&source, &rel))?;
+        let syntax = parse(&text, &display)?;
+        files.push((display, syntax));
+    }
+
+    let syntaxes: Vec<&syn::File> = files.iter().map(|(_, syntax)| syntax).collect();
+    let functions = checked_functions(&syntaxes, settings);
+    let mut violations = Vec::new();
+    for (display, syntax) in &files {
+        violations.extend(check_syntax(syntax, display, settings, functions.as_ref()));
+    }
+    Ok(violations)
+}
+
+// 检查一段源码，file 用于标注违规位置
+pub fn check_source(
+    text: &str,
+    file: &str,
+    settings: &ConstraintSettings,
+) -> io::Result<Vec<Violation>> {
+    let syntax = parse(text, file)?;
+    let functions = checked_functions(&[&syntax], settings);
+    Ok(check_syntax(&syntax, file, settings, functions.as_ref()))
+}
+
+fn parse(text: &str, file: &str) -> io::Result<syn::File> {
+    syn::parse_file(text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}:{}: {}", file, e.span().start().line, e),
+        )
+    })
+}
+
+fn check_syntax(
+    syntax: &syn::File,
+    file: &str,
+    settings: &ConstraintSettings,
+    functions: Option<&HashSet<String>>,
+) -> Vec<Violation> {
+    let mut checker = Checker {
+        settings,
+        functions,
+        file,
+        violations: Vec::new(),
+    };
+    checker.check_items(&syntax.items);
+    checker.violations.sort_by_key(|v| (v.line, v.column));
+    checker.violations
+}
+
+// 需要检查的函数名：settings.functions 及它们直接或间接调用的非测试函数和习题中用 macro_rules! 定义的宏，
+// 否则把运算挪到辅助函数或宏中就能绕过约束；为 None 时检查全部代码
+fn checked_functions(
+    files: &[&syn::File],
+    settings: &ConstraintSettings,
+) -> Option<HashSet<String>> {
+    if settings.functions.is_empty() {
+        return None;
+    }
+    let mut bodies = HashMap::new();
+    let mut macros = HashMap::new();
+    for file in files {
+        collect_functions(&file.items, &mut bodies, &mut macros);
+    }
+
+    let mut checked: HashSet<String> = settings.functions.iter().cloned().collect();
+    let mut pending: Vec<String> = settings.functions.clone();
+    while let Some(name) = pending.pop() {
+        let mut calls = Calls::default();
+        for body in bodies.get(&name).into_iter().flatten() {
+            calls.visit_block(body);
+        }
+        // 宏体无法解析为语法树，其中出现的标识符都可能是调用的函数或宏
+        for tokens in macros.get(&name).into_iter().flatten() {
+            calls.visit_tokens(tokens);
+        }
+        for callee in calls.names {
+            // 同名的函数、方法和宏无法区分，一并检查
+            let defined = bodies.contains_key(&callee) || macros.contains_key(&callee);
+            if defined && checked.insert(callee.clone()) {
+                pending.push(callee);
+            }
+        }
+    }
+    Some(checked)
+}
+
+// 按名称收集测试以外的函数和方法体，以及 macro_rules! 宏的定义
+fn collect_functions<'ast>(
+    items: &'ast [Item],
+    bodies: &mut HashMap<String, Vec<&'ast Block>>,
+    macros: &mut HashMap<String, Vec<&'ast TokenStream>>,
+) {
+    for item in items {
+        match item {
+            Item::Fn(function) if !is_test(&function.attrs) => {
+                let name = function.sig.ident.to_string();
+                bodies.entry(name).or_default().push(&function.block);
+            }
+            Item::Macro(definition) if !is_test(&definition.attrs) => {
+                if let Some(ident) = &definition.ident {
+                    macros
+                        .entry(ident.to_string())
+                        .or_default()
+                        .push(&definition.mac.tokens);
+                }
+            }
+            Item::Impl(block) => {
+                for impl_item in &block.items {
+                    if let ImplItem::Fn(method) = impl_item {
+                        if !is_test(&method.attrs) {
+                            let name = method.sig.ident.to_string();
+                            bodies.entry(name).or_default().push(&method.block);
+                        }
+                    }
+                }
+            }
+            Item::Mod(module) if !is_test(&module.attrs) => {
+                if let Some((_, items)) = &module.content {
+                    collect_functions(items, bodies, macros);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// 函数体中调用或引用的函数名和宏名，包括作为参数传递的函数，如 map(helper)
+#[derive(Default)]
+struct Calls {
+    names: HashSet<String>,
+}
+
+impl Calls {
+    fn visit_tokens(&mut self, tokens: &TokenStream) {
+        for token in tokens.clone() {
+            match token {
+                TokenTree::Ident(ident) => {
+                    self.names.insert(ident.to_string());
+                }
+                TokenTree::Group(group) => self.visit_tokens(&group.stream()),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Calls {
+    fn visit_expr_path(&mut self, path: &'ast syn::ExprPath) {
+        if let Some(last) = path.path.segments.last() {
+            self.names.insert(last.ident.to_string());
+        }
+        visit::visit_expr_path(self, path);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        self.names.insert(call.method.to_string());
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Some(name) = mac.path.segments.last() {
+            self.names.insert(name.ident.to_string());
+        }
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+struct Checker<'a> {
+    settings: &'a ConstraintSettings,
+    // 需要检查的函数，None 表示检查全部代码
+    functions: Option<&'a HashSet<String>>,
+    file: &'a str,
+    violations: Vec<Violation>,
+}
+
+impl Checker<'_> {
+    fn check_items(&mut self, items: &[Item]) {
+        let all_code = self.functions.is_none();
+        for item in items {
+            match item {
+                Item::Fn(function) if self.wanted(&function.sig.ident, &function.attrs) => {
+                    self.visit_item_fn(function);
+                }
+                Item::Fn(_) => {}
+                Item::Impl(block) => {
+                    for impl_item in &block.items {
+                        match impl_item {
+                            ImplItem::Fn(method) if self.wanted(&method.sig.ident, &method.attrs) => {
+                                self.visit_impl_item_fn(method);
+                            }
+                            ImplItem::Fn(_) => {}
+                            other if all_code => self.visit_impl_item(other),
+                            _ => {}
+                        }
+                    }
+                }
+                Item::Mod(module) if !is_test(&module.attrs) => {
+                    if let Some((_, items)) = &module.content {
+                        self.check_items(items);
+                    }
+                }
+                Item::Mod(_) => {}
+                Item::Macro(definition)
+                    if definition
+                        .ident
+                        .as_ref()
+                        .is_some_and(|name| self.wanted(name, &definition.attrs)) =>
+                {
+                    self.visit_item_macro(definition);
+                }
+                other if all_code => self.visit_item(other),
+                _ => {}
+            }
+        }
+    }
+
+    fn wanted(&self, name: &syn::Ident, attrs: &[Attribute]) -> bool {
+        !is_test(attrs)
+            && self
+                .functions
+                .is_none_or(|functions| functions.contains(&name.to_string()))
+    }
+
+    fn report(&mut self, span: Span, message: String) {
+        let start = span.start();
+        self.violations.push(Violation {
+            file: self.file.to_string(),
+            line: start.line,
+            column: start.column + 1,
+            message,
+        });
+    }
+
+    fn check_operator(&mut self, operator: &impl ToTokens, span: Span) {
+        let text = operator.to_token_stream().to_string().replace(' ', "");
+        if self.settings.forbidden_operators.contains(&text) {
+            self.report(span, format!("uses forbidden operator `{}`", text));
+        }
+    }
+
+    // 路径中包含规则的连续若干段即视为使用，如 std::collections::HashSet 匹配 HashSet
+    fn check_path(&mut self, segments: &[String], span: Span) {
+        let settings = self.settings;
+        for rule in &settings.forbidden_paths {
+            if rule.ends_with('!') {
+                continue;
+            }
+            let rule_segments: Vec<&str> = rule.split("::").collect();
+            let matched = segments
+                .windows(rule_segments.len())
+                .any(|window| window.iter().zip(&rule_segments).all(|(a, b)| a == b));
+            if matched {
+                self.report(span, format!("uses forbidden path `{}`", rule));
+            }
+        }
+    }
+
+    // 展开 use 语句，逐个检查导入的路径
+    fn check_use_tree(&mut self, tree: &syn::UseTree, prefix: &mut Vec<String>) {
+        match tree {
+            syn::UseTree::Path(path) => {
+                prefix.push(path.ident.to_string());
+                self.check_use_tree(&path.tree, prefix);
+                prefix.pop();
+            }
+            syn::UseTree::Name(name) => self.check_use_name(prefix, &name.ident),
+            syn::UseTree::Rename(rename) => self.check_use_name(prefix, &rename.ident),
+            syn::UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.check_use_tree(tree, prefix);
+                }
+            }
+            syn::UseTree::Glob(_) => {}
+        }
+    }
+
+    fn check_use_name(&mut self, prefix: &[String], ident: &syn::Ident) {
+        let mut segments = prefix.to_vec();
+        segments.push(ident.to_string());
+        self.check_path(&segments, ident.span());
+    }
+
+    fn check_call(&mut self, name: &syn::Ident) {
+        if self.settings.forbidden_calls.iter().any(|call| name == call) {
+            self.report(name.span(), format!("calls forbidden function `{}`", name));
+        }
+    }
+
+    fn check_macro_name(&mut self, name: &syn::Ident, span: Span) {
+        let name = format!("{}!", name);
+        if self.settings.forbidden_paths.contains(&name) {
+            self.report(span, format!("uses forbidden macro `{}`", name));
+        }
+    }
+
+    // macro_rules! 的宏体无法解析为语法树，逐个词法单元检查：连续的 a::b 视为路径，
+    // 后跟括号的标识符视为调用，后跟 ! 的视为宏，相连的符号视为运算符。
+    // $x 等宏变量和 $(...),* 中的重复符号不是代码，跳过
+    fn check_tokens(&mut self, tokens: &TokenStream) {
+        let tokens: Vec<TokenTree> = tokens.clone().into_iter().collect();
+        let is_punct = |token: Option<&TokenTree>, ch: char| {
+            matches!(token, Some(TokenTree::Punct(p)) if p.as_char() == ch)
+        };
+        let mut i = 0;
+        while i < tokens.len() {
+            match &tokens[i] {
+                TokenTree::Group(group) => self.check_tokens(&group.stream()),
+                TokenTree::Ident(ident) => {
+                    let mut segments = vec![ident.to_string()];
+                    let mut last = ident.clone();
+                    while is_punct(tokens.get(i + 1), ':') && is_punct(tokens.get(i + 2), ':') {
+                        let Some(TokenTree::Ident(next)) = tokens.get(i + 3) else {
+                            break;
+                        };
+                        segments.push(next.to_string());
+                        last = next.clone();
+                        i += 3;
+                    }
+                    self.check_path(&segments, ident.span());
+                    match tokens.get(i + 1) {
+                        Some(TokenTree::Group(group))
+                            if group.delimiter() == Delimiter::Parenthesis =>
+                        {
+                            self.check_call(&last);
+                        }
+                        Some(TokenTree::Punct(p)) if p.as_char() == '!' => {
+                            self.check_macro_name(&last, last.span());
+                        }
+                        _ => {}
+                    }
+                }
+                TokenTree::Punct(punct) if punct.as_char() == '$' => {
+                    match tokens.get(i + 1) {
+                        // 宏变量
+                        Some(TokenTree::Ident(_)) => i += 1,
+                        // 重复：可选的分隔符和 *、+、?
+                        Some(TokenTree::Group(group)) => {
+                            self.check_tokens(&group.stream());
+                            i += 1;
+                            let is_repetition = |token: Option<&TokenTree>| {
+                                ['*', '+', '?'].iter().any(|&ch| is_punct(token, ch))
+                            };
+                            if is_repetition(tokens.get(i + 1)) {
+                                i += 1;
+                            } else if is_repetition(tokens.get(i + 2)) {
+                                i += 2;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                TokenTree::Punct(punct) => {
+                    let mut operator = punct.as_char().to_string();
+                    let mut spacing = punct.spacing();
+                    while spacing == Spacing::Joint {
+                        let Some(TokenTree::Punct(next)) = tokens.get(i + 1) else {
+                            break;
+                        };
+                        operator.push(next.as_char());
+                        spacing = next.spacing();
+                        i += 1;
+                    }
+                    if self.settings.forbidden_operators.contains(&operator) {
+                        self.report(
+                            punct.span(),
+                            format!("uses forbidden operator `{}`", operator),
+                        );
+                    }
+                }
+                TokenTree::Literal(_) => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Checker<'_> {
+    fn visit_bin_op(&mut self, operator: &'ast syn::BinOp) {
+        self.check_operator(operator, operator.span());
+    }
+
+    fn visit_un_op(&mut self, operator: &'ast syn::UnOp) {
+        self.check_operator(operator, operator.span());
+    }
+
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        self.check_path(&segments, path.span());
+        visit::visit_path(self, path);
+    }
+
+    fn visit_item_use(&mut self, item: &'ast syn::ItemUse) {
+        self.check_use_tree(&item.tree, &mut Vec::new());
+    }
+
+    // macro_rules! 定义的宏体按词法单元检查
+    fn visit_item_macro(&mut self, item: &'ast syn::ItemMacro) {
+        if item.ident.is_some() {
+            self.check_tokens(&item.mac.tokens);
+        } else {
+            self.visit_macro(&item.mac);
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Some(name) = mac.path.segments.last() {
+            self.check_macro_name(&name.ident, mac.path.span());
+        }
+        // println!、assert! 等宏的参数是逗号分隔的表达式，继续检查其中的代码
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        self.check_call(&call.method);
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let Expr::Path(path) = &*call.func {
+            if let Some(last) = path.path.segments.last() {
+                self.check_call(&last.ident);
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+}
+
+// #[test] 函数和 #[cfg(test)] 模块不检查
+pub(crate) fn is_test(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("test")
+            || (attr.path().is_ident("cfg")
+                && attr
+                    .parse_args::<syn::Ident>()
+                    .is_ok_and(|ident| ident == "test"))
+    })
+}
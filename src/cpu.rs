@@ -0,0 +1,169 @@
+use std::io::{self, Read};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 被终止的进程仍有后代持有输出管道时，最多再等待这么久
+const PIPE_GRACE: Duration = Duration::from_secs(1);
+
+// 子进程结束后的输出和 CPU 时间，timed_out 表示超时后被终止
+pub(crate) struct Finished {
+    pub output: Output,
+    pub cpu_time: Option<Duration>,
+    pub timed_out: bool,
+}
+
+// 运行命令并等待结束，返回其输出和 CPU 时间；超过 timeout 时终止该命令启动的全部进程
+pub(crate) fn output(command: &mut Command, timeout: Option<Duration>) -> io::Result<Finished> {
+    if timeout.is_some() {
+        own_process_group(command);
+    }
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    wait_with_output(child, timeout)
+}
+
+// 让命令在新的进程组中运行，超时时连同它启动的编译器、构建脚本和测试程序一起终止
+pub(crate) fn own_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(not(unix))]
+    let _ = command;
+}
+
+// 读取子进程的全部输出并等待其结束；CPU 时间为子进程及其已回收的后代进程的用户态与内核态时间之和，
+// 不受机器负载影响，不支持的平台上为 None。超过 timeout 时终止子进程及其进程组
+pub(crate) fn wait_with_output(
+    mut child: Child,
+    timeout: Option<Duration>,
+) -> io::Result<Finished> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    drop(child.stdin.take());
+    // 两个管道同时读取，避免子进程写满其中一个而阻塞
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+    let (status, cpu_time, timed_out) = wait(&mut child, deadline)?;
+    Ok(Finished {
+        output: Output {
+            status,
+            stdout: collect(stdout, deadline)?,
+            stderr: collect(stderr, deadline)?,
+        },
+        cpu_time,
+        timed_out,
+    })
+}
+
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> mpsc::Receiver<io::Result<Vec<u8>>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = sender.send(pipe.read_to_end(&mut buffer).map(|_| buffer));
+    });
+    receiver
+}
+
+// 有期限时脱离进程组的后代可能一直不关闭管道，期限过后最多再等待 PIPE_GRACE
+fn collect(
+    reader: Option<mpsc::Receiver<io::Result<Vec<u8>>>>,
+    deadline: Option<Instant>,
+) -> io::Result<Vec<u8>> {
+    let Some(reader) = reader else {
+        return Ok(Vec::new());
+    };
+    match deadline {
+        Some(deadline) => {
+            let wait = deadline.saturating_duration_since(Instant::now()) + PIPE_GRACE;
+            reader.recv_timeout(wait).unwrap_or_else(|_| Ok(Vec::new()))
+        }
+        None => reader
+            .recv()
+            .map_err(|_| io::Error::other("output reader panicked"))?,
+    }
+}
+
+// 等待子进程结束；到达期限前按逐渐增大的间隔轮询，确保只终止尚未回收的进程
+fn wait(
+    child: &mut Child,
+    deadline: Option<Instant>,
+) -> io::Result<(ExitStatus, Option<Duration>, bool)> {
+    let Some(deadline) = deadline else {
+        let (status, cpu_time) = reap(child, true)?.expect("blocking wait returns a status");
+        return Ok((status, cpu_time, false));
+    };
+    let mut interval = Duration::from_millis(1);
+    loop {
+        if let Some((status, cpu_time)) = reap(child, false)? {
+            return Ok((status, cpu_time, false));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            kill(child);
+            let (status, cpu_time) = reap(child, true)?.expect("blocking wait returns a status");
+            return Ok((status, cpu_time, true));
+        }
+        thread::sleep(interval.min(deadline - now));
+        interval = (interval * 2).min(Duration::from_millis(50));
+    }
+}
+
+#[cfg(unix)]
+fn reap(child: &mut Child, block: bool) -> io::Result<Option<(ExitStatus, Option<Duration>)>> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let flags = if block { 0 } else { libc::WNOHANG };
+    let mut status = 0;
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        let pid = unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, flags, &mut usage) };
+        if pid == 0 {
+            return Ok(None);
+        }
+        if pid > 0 {
+            break;
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+    let cpu_time = duration(usage.ru_utime) + duration(usage.ru_stime);
+    Ok(Some((ExitStatus::from_raw(status), Some(cpu_time))))
+}
+
+// 子进程尚未回收，其进程号不会被复用；不是进程组组长时终止进程组会失败，不影响结果
+#[cfg(unix)]
+fn kill(child: &mut Child) {
+    let pid = child.id() as libc::pid_t;
+    unsafe {
+        libc::kill(-pid, libc::SIGKILL);
+        libc::kill(pid, libc::SIGKILL);
+    }
+}
+
+#[cfg(unix)]
+fn duration(time: libc::timeval) -> Duration {
+    Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+}
+
+#[cfg(not(unix))]
+fn reap(child: &mut Child, block: bool) -> io::Result<Option<(ExitStatus, Option<Duration>)>> {
+    let status = if block {
+        Some(child.wait()?)
+    } else {
+        child.try_wait()?
+    };
+    Ok(status.map(|status| (status, None)))
+}
+
+#[cfg(not(unix))]
+fn kill(child: &mut Child) {
+    let _ = child.kill();
+}
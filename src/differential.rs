@@ -0,0 +1,228 @@
+use crate::instrument;
+use crate::sandbox::{self, Sandbox};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+// 驱动程序输出反例所用的标记，libtest 可能在同一行之前输出测试名
+const REPORT_MARKER: &str = "__CARGOTEST_DIFFERENTIAL__ ";
+
+const DRIVER_TEST: &str = "__cargotest_differential::run";
+
+// 追加到习题源码末尾的驱动程序，{harness} 替换为对照实现文件的路径，{rng} 替换为随机数生成器
+const DRIVER: &str = r#"
+mod __cargotest_differential {
+    #[path = {harness}]
+    mod harness;
+
+    {rng}
+    fn env_u64(name: &str) -> u64 {
+        std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(0)
+    }
+
+    // 两个实现结果不一致时返回 (期望值, 实际值)
+    fn mismatch(input: &harness::Input) -> Option<(String, String)> {
+        let expected = harness::reference(input);
+        let actual = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            harness::candidate(input)
+        }));
+        match actual {
+            Ok(ref actual) if *actual == expected => None,
+            Ok(actual) => Some((format!("{:?}", expected), format!("{:?}", actual))),
+            Err(_) => Some((format!("{:?}", expected), "<panicked>".to_string())),
+        }
+    }
+
+    // 不断用仍然失败的更小输入替换当前反例
+    fn minimize(mut input: harness::Input) -> harness::Input {
+        loop {
+            match harness::shrink(&input).into_iter().find(|c| mismatch(c).is_some()) {
+                Some(smaller) => input = smaller,
+                None => return input,
+            }
+        }
+    }
+
+    #[test]
+    fn run() {
+        let seed = env_u64("CARGOTEST_SEED");
+        let cases = env_u64("CARGOTEST_CASES");
+        // 设置时只输出该用例的输入和期望值，不运行习题解答
+        let describe = std::env::var("CARGOTEST_DESCRIBE").ok().and_then(|v| v.parse::<u64>().ok());
+        std::panic::set_hook(Box::new(|_| {}));
+
+        let mut rng = Rng::new(seed);
+        for case in 0..cases {
+            let input = harness::generate(&mut rng);
+            if let Some(target) = describe {
+                if case == target {
+                    println!("__CARGOTEST_DIFFERENTIAL__ input={:?}", input);
+                    println!("__CARGOTEST_DIFFERENTIAL__ expected={:?}", harness::reference(&input));
+                    return;
+                }
+                continue;
+            }
+            // 超时被终止或崩溃时据此找出出错的用例
+            println!("__CARGOTEST_DIFFERENTIAL__ running={}", case);
+            if mismatch(&input).is_none() {
+                continue;
+            }
+            let input = minimize(input);
+            let (expected, actual) = mismatch(&input).unwrap();
+            println!("__CARGOTEST_DIFFERENTIAL__ case={}", case);
+            println!("__CARGOTEST_DIFFERENTIAL__ input={:?}", input);
+            println!("__CARGOTEST_DIFFERENTIAL__ expected={}", expected);
+            println!("__CARGOTEST_DIFFERENTIAL__ actual={}", actual);
+            panic!("counterexample found");
+        }
+    }
+}
+"#;
+
+// 差分测试配置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DifferentialSettings {
+    // 对照实现文件，相对于仓库根目录
+    pub harness: String,
+    #[serde(default = "default_cases")]
+    pub cases: u64,
+    #[serde(default)]
+    pub seed: u64,
+    // 全部用例的运行时间上限，超时即终止程序，卡住的用例作为反例
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_cases() -> u64 {
+    1000
+}
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+// 最小化后的反例
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Counterexample {
+    pub seed: u64,
+    pub case: u64,
+    pub input: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Counterexample {
+    // 无法得到具体输入时（例如驱动程序无法编译或运行）记录的反例，actual 为失败的原因
+    pub fn unknown(settings: &DifferentialSettings, reason: &str) -> Self {
+        Counterexample {
+            seed: seed(settings),
+            case: 0,
+            input: "<unknown>".to_string(),
+            expected: "<unknown>".to_string(),
+            actual: format!("<{}>", reason),
+        }
+    }
+}
+
+// 使用的随机种子，环境变量 CARGOTEST_SEED 可覆盖配置中的种子
+fn seed(settings: &DifferentialSettings) -> u64 {
+    std::env::var("CARGOTEST_SEED")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(settings.seed)
+}
+
+// 用同一串随机输入对比习题解答与对照实现
+pub fn check_single_file(
+    root: &Path,
+    file_path: &Path,
+    settings: &DifferentialSettings,
+    sandbox: Option<&Sandbox>,
+) -> io::Result<Option<Counterexample>> {
+    let seed = seed(settings);
+
+    let harness = fs::canonicalize(root.join(&settings.harness))?;
+    let driver = DRIVER
+        .replace("{rng}", instrument::RNG)
+        .replace("{harness}", &format!("{:?}", harness.display().to_string()));
+    let instrumented = instrument::build_single_file(
+        root,
+        file_path,
+        &driver,
+        "differential",
+        std::slice::from_ref(&harness),
+        sandbox,
+    )?;
+
+    let timeout = Duration::from_millis(settings.timeout_ms);
+    let run = |describe: Option<u64>| {
+        let mut command = Command::new(&instrumented.binary);
+        command
+            .args(["--exact", DRIVER_TEST, "--nocapture", "--test-threads=1"])
+            .env("CARGOTEST_SEED", seed.to_string())
+            .env("CARGOTEST_CASES", settings.cases.to_string());
+        if let Some(case) = describe {
+            command.env("CARGOTEST_DESCRIBE", case.to_string());
+        }
+        sandbox::output(&mut command, sandbox, Some(timeout))
+    };
+
+    let finished = run(None)?;
+    if finished.output.status.success() {
+        return Ok(None);
+    }
+    let stdout = String::from_utf8_lossy(&finished.output.stdout).into_owned();
+
+    if let (Some(case), Some(input), Some(expected), Some(actual)) = (
+        field(&stdout, "case").pop(),
+        field(&stdout, "input").pop(),
+        field(&stdout, "expected").pop(),
+        field(&stdout, "actual").pop(),
+    ) {
+        return Ok(Some(Counterexample {
+            seed,
+            case: case.parse().unwrap_or(0),
+            input,
+            expected,
+            actual,
+        }));
+    }
+
+    // 超时或崩溃（栈溢出、abort）时重新生成出错的用例的输入，不再运行习题解答
+    let reason = if finished.timed_out {
+        format!("timed out after {} ms", settings.timeout_ms)
+    } else {
+        "crashed".to_string()
+    };
+    let Some(case) = field(&stdout, "running")
+        .last()
+        .and_then(|case| case.parse().ok())
+    else {
+        return Err(io::Error::other(format!(
+            "differential driver {} without a counterexample",
+            reason
+        )));
+    };
+    let described = run(Some(case))?;
+    let described = String::from_utf8_lossy(&described.output.stdout).into_owned();
+    Ok(Some(Counterexample {
+        seed,
+        case,
+        input: field(&described, "input").pop().unwrap_or_else(|| "<unknown>".to_string()),
+        expected: field(&described, "expected").pop().unwrap_or_else(|| "<unknown>".to_string()),
+        actual: format!("<{}>", reason),
+    }))
+}
+
+// 驱动程序输出中某个字段的全部取值
+fn field(stdout: &str, key: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter_map(|line| line.split_once(REPORT_MARKER).map(|(_, rest)| rest))
+        .filter_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 生成报告时的工具链与运行环境
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Environment {
+    pub rustc: Option<String>,
+    pub cargo: Option<String>,
+    pub clippy: Option<String>,
+    // rustc 的宿主目标三元组，如 x86_64-unknown-linux-gnu
+    pub host: Option<String>,
+    pub os: String,
+    pub cpus: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitState>,
+    // Unix 时间戳（秒）
+    pub started_at: u64,
+    #[serde(default)]
+    pub finished_at: u64,
+    // 评测程序的命令行参数
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitState {
+    pub commit: String,
+    // 工作区是否有未提交的修改
+    pub dirty: bool,
+}
+
+impl Environment {
+    // 在评测开始时记录环境信息，命令在 root 下执行以使用仓库指定的工具链
+    pub fn capture(root: &Path) -> Self {
+        let verbose = command_output(root, "rustc", &["-vV"]);
+        let host = verbose.as_deref().and_then(|text| {
+            text.lines()
+                .find_map(|line| line.strip_prefix("host: "))
+                .map(str::to_string)
+        });
+
+        Self {
+            rustc: command_output(root, "rustc", &["--version"]),
+            cargo: command_output(root, "cargo", &["--version"]),
+            clippy: command_output(root, "cargo", &["clippy", "--version"]),
+            host,
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            cpus: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            git: git_state(root),
+            started_at: unix_time(),
+            finished_at: 0,
+            args: std::env::args().skip(1).collect(),
+        }
+    }
+
+    // 记录的 rustc 版本号，如 (1, 95, 0)
+    pub fn rustc_release(&self) -> Option<(u32, u32, u32)> {
+        let version = self.rustc.as_deref()?.split_whitespace().nth(1)?;
+        parse_version(version)
+    }
+
+    // 检查 rustc 版本是否不低于 minimum
+    pub fn check_min_rustc(&self, minimum: &str) -> Result<(), String> {
+        let required = parse_version(minimum)
+            .ok_or_else(|| format!("invalid rustc version {}", minimum))?;
+        match self.rustc_release() {
+            Some(found) if found >= required => Ok(()),
+            Some(_) => Err(format!(
+                "{} is older than the required rustc {}",
+                self.rustc.as_deref().unwrap_or_default(),
+                minimum
+            )),
+            None => Err("could not determine the rustc version".to_string()),
+        }
+    }
+}
+
+// 解析 1.70、1.70.0 或 1.95.0-nightly 形式的版本号
+pub fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let version = version.split('-').next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u32>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next()?.ok()?;
+    let patch = match parts.next() {
+        Some(patch) => patch.ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+pub(crate) fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// 执行命令并返回去掉首尾空白的标准输出，失败时返回 None
+pub(crate) fn command_output(root: &Path, program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn git_state(root: &Path) -> Option<GitState> {
+    let commit = command_output(root, "git", &["rev-parse", "HEAD"])?;
+    let status = command_output(root, "git", &["status", "--porcelain"])?;
+    Some(GitState {
+        commit,
+        dirty: !status.is_empty(),
+    })
+}
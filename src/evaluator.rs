@@ -0,0 +1,490 @@
+use crate::config::{Exercise, ExerciseConfig};
+use crate::constraints::{self, Violation};
+use crate::complexity::{self, ComplexityEstimate};
+use crate::differential::{self, Counterexample};
+use crate::environment::{self, Environment};
+use crate::hint;
+use crate::i18n::{Lang, Messages};
+use crate::leak::{self, LeakCheck, TestLeak};
+use crate::logs::{self, StepLogger};
+use crate::registry::{Context, EvaluatorRegistry, ExerciseEvaluator};
+use crate::report::{Attempt, ExerciseResult, Report};
+use crate::sandbox::Sandbox;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// 评测过程中输出一行信息
+pub(crate) type Log<'a> = dyn FnMut(String) + 'a;
+
+// 评测进度事件
+pub enum Progress<'a> {
+    Started { exercise: &'a Exercise },
+    Output { exercise: &'a Exercise, line: String },
+    Finished { exercise: &'a Exercise, result: &'a ExerciseResult },
+}
+
+type ProgressCallback = Box<dyn FnMut(Progress) + Send>;
+
+// 习题评测器，root 为包含 exercises 目录的仓库根目录
+pub struct Evaluator {
+    root: PathBuf,
+    hint_penalty: i32,
+    retries: u32,
+    sandbox: bool,
+    timeout: Option<Duration>,
+    msg: Messages,
+    min_rustc: Option<String>,
+    // 工具链版本检查的结果，首次需要时检查
+    toolchain: Option<Result<(), String>>,
+    // 本次运行的日志编号
+    run_id: String,
+    registry: EvaluatorRegistry,
+    progress: Option<ProgressCallback>,
+}
+
+impl Evaluator {
+    pub fn new(root: impl Into<PathBuf>, config: &ExerciseConfig) -> Self {
+        let root = root.into();
+        Self {
+            run_id: logs::start_run(&root),
+            root,
+            hint_penalty: config.hint_penalty,
+            retries: config.retries,
+            sandbox: config.sandbox,
+            timeout: config.timeout_secs.map(Duration::from_secs),
+            msg: Messages::default(),
+            min_rustc: config.min_rustc.clone(),
+            toolchain: None,
+            registry: EvaluatorRegistry::for_config(config),
+            progress: None,
+        }
+    }
+
+    // 注册或替换某种习题类型的评测方式
+    pub fn register(
+        mut self,
+        exercise_type: impl Into<String>,
+        evaluator: impl ExerciseEvaluator + 'static,
+    ) -> Self {
+        self.registry.register(exercise_type, evaluator);
+        self
+    }
+
+    // 设置评测信息使用的语言，默认为英文
+    pub fn lang(mut self, lang: Lang) -> Self {
+        self.msg = Messages::new(lang);
+        self
+    }
+
+    // 配置中没有指定 timeout_secs 时每道题评测时间的上限
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout.get_or_insert(timeout);
+        self
+    }
+
+    // 设置进度回调
+    pub fn on_progress(mut self, callback: impl FnMut(Progress) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // 检查 rustc 版本是否满足配置的 min_rustc；版本过低时不评测任何习题
+    pub fn check_toolchain(&mut self) -> Result<(), String> {
+        let (root, minimum) = (&self.root, &self.min_rustc);
+        self.toolchain
+            .get_or_insert_with(|| match minimum {
+                Some(minimum) => Environment {
+                    rustc: environment::command_output(root, "rustc", &["--version"]),
+                    ..Environment::default()
+                }
+                .check_min_rustc(minimum),
+                None => Ok(()),
+            })
+            .clone()
+    }
+
+    // 依次评测配置中的全部习题
+    pub fn evaluate_all(&mut self, config: &ExerciseConfig) -> Report {
+        let start_time = Instant::now();
+        let mut report = Report::new();
+        report.environment = Some(Environment::capture(&self.root));
+        for exercise in config.all_exercises() {
+            report.record(self.evaluate(&exercise));
+        }
+        report.finish(config, start_time.elapsed());
+        report
+    }
+
+    pub fn evaluate(&mut self, exercise: &Exercise) -> ExerciseResult {
+        self.emit(Progress::Started { exercise });
+
+        let toolchain = self.check_toolchain();
+        let root = self.root.clone();
+        let hint_penalty = self.hint_penalty;
+        let msg = self.msg;
+        let retries = exercise.retries.unwrap_or(self.retries);
+        let mut progress = self.progress.take();
+        let mut log = |line: String| {
+            if let Some(callback) = progress.as_mut() {
+                callback(Progress::Output { exercise, line });
+            }
+        };
+
+        let mut evaluator = match self.registry.get(&exercise.exercise_type) {
+            Some(evaluator) => Some(evaluator),
+            None => {
+                log(msg.format("eval.unknown_type", &[&exercise.exercise_type]));
+                None
+            }
+        };
+        if let Err(e) = toolchain {
+            log(format!("\x1b[31m{}\x1b[0m", msg.format("toolchain.too_old", &[&e])));
+            evaluator = None;
+        }
+
+        // 要求沙箱却无法建立时不运行不可信的代码
+        let sandbox = if exercise.sandbox.unwrap_or(self.sandbox) {
+            match Sandbox::for_exercise(&root, exercise) {
+                Ok(sandbox) => Some(sandbox),
+                Err(e) => {
+                    let line = msg.format("eval.sandbox_unavailable", &[&exercise.name, &e]);
+                    log(format!("\x1b[31m{}\x1b[0m", line));
+                    evaluator = None;
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // 未通过时按配置重试，记录每次的结果与耗时
+        let mut attempts = Vec::new();
+        let mut cpu_budget_exceeded = false;
+        let timeout = exercise.timeout_secs.map(Duration::from_secs).or(self.timeout);
+        let mut steps = StepLogger::new(&root, &self.run_id, &exercise.name)
+            .sandboxed(sandbox.clone())
+            .time_limit(timeout);
+        if let Some(evaluator) = evaluator {
+            loop {
+                let start_time = Instant::now();
+                let first_step = steps.step_count();
+                let mut passed =
+                    evaluator.evaluate(&mut Context::new(&root, exercise, msg, &mut log, &mut steps));
+                let cpu_time = steps.cpu_time_since(first_step);
+                cpu_budget_exceeded = false;
+                if let (Some(budget), Some(cpu_time)) = (exercise.cpu_budget_ms, cpu_time) {
+                    let cpu_ms = cpu_time.as_millis() as u64;
+                    if cpu_ms > budget {
+                        let line =
+                            msg.format("eval.cpu_exceeded", &[&exercise.name, &cpu_ms, &budget]);
+                        log(format!("\x1b[31m{}\x1b[0m", line));
+                        cpu_budget_exceeded = true;
+                        passed = false;
+                    } else if passed {
+                        let line =
+                            msg.format("eval.cpu_within", &[&exercise.name, &cpu_ms, &budget]);
+                        log(format!("\x1b[32m{}\x1b[0m", line));
+                    }
+                }
+                attempts.push(Attempt {
+                    passed,
+                    duration_ms: start_time.elapsed().as_millis() as u64,
+                    cpu_ms: cpu_time.map(|time| time.as_millis() as u64),
+                });
+                // 超过时间上限后不再重试
+                if passed || attempts.len() > retries as usize || timed_out(&steps) {
+                    break;
+                }
+                let line = msg.format(
+                    "eval.retrying",
+                    &[&exercise.name, &(attempts.len() + 1), &(retries + 1)],
+                );
+                log(format!("\x1b[33m{}\x1b[0m", line));
+            }
+        }
+        let mut result = attempts.last().is_some_and(|a| a.passed);
+        let timed_out = timed_out(&steps);
+        if timed_out {
+            let limit = timeout.unwrap_or_default().as_secs();
+            let line = msg.format("eval.timed_out", &[&exercise.name, &limit]);
+            log(format!("\x1b[31m{}\x1b[0m", line));
+            result = false;
+        }
+        let flaky = result && attempts.len() > 1;
+        if flaky {
+            let line = msg.format("eval.flaky", &[&exercise.name, &attempts.len()]);
+            log(format!("\x1b[33m{}\x1b[0m", line));
+        }
+
+        let violations = if result {
+            check_constraints(&root, exercise, msg, &mut log)
+        } else {
+            Vec::new()
+        };
+        if !violations.is_empty() {
+            result = false;
+        }
+
+        let (leaks, leaks_passed) = if result {
+            check_leaks(&root, exercise, sandbox.as_ref(), steps.deadline(), msg, &mut log)
+        } else {
+            (Vec::new(), true)
+        };
+        if !leaks_passed {
+            result = false;
+        }
+
+        let counterexample = if result {
+            run_differential(&root, exercise, sandbox.as_ref(), msg, &mut log)
+        } else {
+            None
+        };
+        if counterexample.is_some() {
+            result = false;
+        }
+
+        let (complexity, complexity_passed) = if result {
+            estimate_complexity(&root, exercise, sandbox.as_ref(), msg, &mut log)
+        } else {
+            (None, true)
+        };
+        if !complexity_passed {
+            result = false;
+        }
+
+        // 通过的题目按已查看的提示层数扣分，最低为 0
+        let hints_used = hint::hints_used(&root, &exercise.name);
+        let score = if result {
+            (exercise.score - hint_penalty * hints_used as i32).max(0)
+        } else {
+            0
+        };
+
+        let logs = steps.finish();
+        let sandbox_violations: Vec<String> = logs
+            .iter()
+            .filter_map(|step| Some(format!("{}: {}", step.step, step.violation.as_ref()?)))
+            .collect();
+        for violation in &sandbox_violations {
+            let line = msg.format("eval.sandbox_violation", &[&exercise.name, violation]);
+            log(format!("\x1b[31m{}\x1b[0m", line));
+        }
+
+        let result = ExerciseResult {
+            name: exercise.name.clone(),
+            result,
+            score,
+            hints_used,
+            leaks,
+            counterexample,
+            violations,
+            complexity,
+            sandbox_violations,
+            cpu_ms: attempts.last().and_then(|a| a.cpu_ms),
+            cpu_budget_exceeded,
+            timed_out,
+            attempts,
+            flaky,
+            logs,
+            resumed: false,
+        };
+
+        self.progress = progress;
+        self.emit(Progress::Finished { exercise, result: &result });
+        result
+    }
+
+    fn emit(&mut self, event: Progress) {
+        if let Some(callback) = self.progress.as_mut() {
+            callback(event);
+        }
+    }
+}
+
+fn timed_out(steps: &StepLogger) -> bool {
+    steps.remaining() == Some(Duration::ZERO)
+}
+
+// 按习题配置检查源码中禁止使用的运算符、类型和函数；无法完成检查时记为一处违规，该题不通过
+fn check_constraints(
+    root: &Path,
+    exercise: &Exercise,
+    msg: Messages,
+    log: &mut Log,
+) -> Vec<Violation> {
+    let settings = match &exercise.constraints {
+        Some(settings) => settings,
+        None => return Vec::new(),
+    };
+
+    match constraints::check_exercise(root, exercise, settings) {
+        Ok(violations) if violations.is_empty() => {
+            let line = msg.format("eval.constraints_passed", &[&exercise.name]);
+            log(format!("\x1b[32m{}\x1b[0m", line));
+            violations
+        }
+        Ok(violations) => {
+            for violation in &violations {
+                log(format!(
+                    "\x1b[31m{}:{}:{}: {}\x1b[0m",
+                    violation.file, violation.line, violation.column, violation.message
+                ));
+            }
+            violations
+        }
+        Err(e) => {
+            let line = msg.format("eval.constraints_error", &[&exercise.name, &e]);
+            log(format!("\x1b[31m{}\x1b[0m", line));
+            vec![Violation {
+                file: format!("exercises/{}", exercise.path),
+                line: 0,
+                column: 0,
+                message: format!("constraint check failed: {}", e),
+            }]
+        }
+    }
+}
+
+// 按习题配置检查内存泄漏，目前仅支持单文件习题。返回发现的泄漏以及是否通过：
+// leak_check 为 fail 时，存在泄漏或检查本身失败（编译失败、被沙箱终止、超时）都判为不通过
+fn check_leaks(
+    root: &Path,
+    exercise: &Exercise,
+    sandbox: Option<&Sandbox>,
+    deadline: Option<Instant>,
+    msg: Messages,
+    log: &mut Log,
+) -> (Vec<TestLeak>, bool) {
+    if exercise.leak_check == LeakCheck::Off || exercise.exercise_type != "single_file" {
+        return (Vec::new(), true);
+    }
+
+    let fail = exercise.leak_check == LeakCheck::Fail;
+    let color = if fail { 31 } else { 33 };
+    let source = exercise.source_path(root);
+    let leaks = match leak::check_single_file(root, &source, sandbox, deadline) {
+        Ok(Some(leaks)) => leaks,
+        Ok(None) => {
+            let line = msg.format("eval.leak_skipped", &[&exercise.name]);
+            log(format!("\x1b[33m{}\x1b[0m", line));
+            return (Vec::new(), true);
+        }
+        Err(e) => {
+            let line = msg.format("eval.leak_error", &[&exercise.name, &e]);
+            log(format!("\x1b[{}m{}\x1b[0m", color, line));
+            return (Vec::new(), !fail);
+        }
+    };
+
+    for leak in &leaks {
+        let line = msg.format(
+            "eval.leaked",
+            &[&exercise.name, &leak.test, &leak.allocations, &leak.bytes],
+        );
+        log(format!("\x1b[{}m{}\x1b[0m", color, line));
+    }
+    let passed = leaks.is_empty() || !fail;
+    (leaks, passed)
+}
+
+// 按习题配置与对照实现进行差分测试，目前仅支持单文件习题
+fn run_differential(
+    root: &Path,
+    exercise: &Exercise,
+    sandbox: Option<&Sandbox>,
+    msg: Messages,
+    log: &mut Log,
+) -> Option<Counterexample> {
+    let settings = exercise.differential.as_ref()?;
+    if exercise.exercise_type != "single_file" {
+        return None;
+    }
+
+    match differential::check_single_file(root, &exercise.source_path(root), settings, sandbox) {
+        Ok(None) => {
+            let line = msg.format("eval.differential_passed", &[&exercise.name, &settings.cases]);
+            log(format!("\x1b[32m{}\x1b[0m", line));
+            None
+        }
+        Ok(Some(counterexample)) => {
+            let line = msg.format(
+                "eval.counterexample",
+                &[&exercise.name, &counterexample.case, &counterexample.seed],
+            );
+            log(format!("\x1b[31m{}\x1b[0m", line));
+            log(msg.format("eval.counterexample_input", &[&counterexample.input]));
+            log(msg.format("eval.counterexample_expected", &[&counterexample.expected]));
+            log(msg.format("eval.counterexample_actual", &[&counterexample.actual]));
+            log(msg.format("eval.counterexample_seed", &[&counterexample.seed]));
+            Some(counterexample)
+        }
+        // 无法完成差分测试同样判为不通过
+        Err(e) => {
+            let line = msg.format("eval.differential_error", &[&exercise.name, &e]);
+            log(format!("\x1b[31m{}\x1b[0m", line));
+            Some(Counterexample::unknown(settings, &e.to_string()))
+        }
+    }
+}
+
+// 按习题配置估计时间复杂度并与题目要求比较，目前仅支持单文件习题。
+// 返回估计结果以及是否通过：设置了 fail 时，超出目标或无法完成测量（结果错误、崩溃、被沙箱终止）都判为不通过
+fn estimate_complexity(
+    root: &Path,
+    exercise: &Exercise,
+    sandbox: Option<&Sandbox>,
+    msg: Messages,
+    log: &mut Log,
+) -> (Option<ComplexityEstimate>, bool) {
+    let Some(settings) = exercise.complexity.as_ref() else {
+        return (None, true);
+    };
+    if exercise.exercise_type != "single_file" {
+        return (None, true);
+    }
+
+    let color = if settings.fail { 31 } else { 33 };
+    let source = exercise.source_path(root);
+    let estimate = match complexity::check_single_file(root, &source, settings, sandbox) {
+        Ok(Some(estimate)) => estimate,
+        Ok(None) => {
+            let line = msg.format("eval.complexity_too_few", &[&exercise.name]);
+            log(format!("\x1b[{}m{}\x1b[0m", color, line));
+            return (None, !settings.fail);
+        }
+        Err(e) => {
+            let line = msg.format("eval.complexity_error", &[&exercise.name, &e]);
+            log(format!("\x1b[{}m{}\x1b[0m", color, line));
+            return (None, !settings.fail);
+        }
+    };
+
+    for sample in &estimate.samples {
+        let size = format!("{:>8}", sample.size);
+        let nanos = format!("{:.0}", sample.nanos);
+        log(msg.format("eval.complexity_sample", &[&size, &nanos]));
+    }
+    if let Some(size) = estimate.timed_out {
+        let size = format!("{:>8}", size);
+        log(msg.format("eval.complexity_timeout", &[&size, &settings.timeout_ms]));
+    }
+    if estimate.within_target() {
+        let line = msg.format(
+            "eval.complexity_within",
+            &[&exercise.name, &estimate.estimated, &estimate.target],
+        );
+        log(format!("\x1b[32m{}\x1b[0m", line));
+    } else {
+        let line = msg.format(
+            "eval.complexity_exceeds",
+            &[&exercise.name, &estimate.estimated, &estimate.target],
+        );
+        log(format!("\x1b[{}m{}\x1b[0m", color, line));
+    }
+    let passed = estimate.within_target() || !settings.fail;
+    (Some(estimate), passed)
+}
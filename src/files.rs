@@ -0,0 +1,47 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// 列出习题包含的文件（相对路径），跳过 target 目录；单文件习题返回空路径
+pub(crate) fn relative_files(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if root.is_file() {
+        files.push(PathBuf::new());
+    } else if root.is_dir() {
+        collect_files(root, Path::new(""), &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files(root: &Path, rel: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(root.join(rel))? {
+        let entry = entry?;
+        let rel_path = rel.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            if entry.file_name() != "target" {
+                collect_files(root, &rel_path, files)?;
+            }
+        } else {
+            files.push(rel_path);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn resolve(root: &Path, rel: &Path) -> PathBuf {
+    if rel.as_os_str().is_empty() {
+        root.to_path_buf()
+    } else {
+        root.join(rel)
+    }
+}
+
+// 复制文件，按需创建目标目录
+pub(crate) fn copy_file(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(from, to)?;
+    Ok(())
+}
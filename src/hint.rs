@@ -0,0 +1,44 @@
+use crate::config::Exercise;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+// 记录每道题已查看的提示层数，相对于仓库根目录
+pub(crate) const HINT_USAGE_FILE: &str = ".cargotest/hints.json";
+
+// 多显示一层提示，返回新显示的层数；全部提示都已显示时返回 None
+pub fn reveal_next_hint(root: &Path, exercise: &Exercise) -> io::Result<Option<usize>> {
+    let mut usage = load_hint_usage(root);
+    let revealed = usage.entry(exercise.name.clone()).or_insert(0);
+    if *revealed >= exercise.hints.len() {
+        return Ok(None);
+    }
+    *revealed += 1;
+    let level = *revealed;
+    save_hint_usage(root, &usage)?;
+    Ok(Some(level))
+}
+
+// 某道题已查看的提示层数
+pub fn hints_used(root: &Path, name: &str) -> usize {
+    load_hint_usage(root).get(name).copied().unwrap_or(0)
+}
+
+// 读取提示使用记录，文件不存在时视为未使用任何提示
+pub fn load_hint_usage(root: &Path) -> BTreeMap<String, usize> {
+    File::open(root.join(HINT_USAGE_FILE))
+        .ok()
+        .and_then(|file| serde_json::from_reader(file).ok())
+        .unwrap_or_default()
+}
+
+fn save_hint_usage(root: &Path, usage: &BTreeMap<String, usize>) -> io::Result<()> {
+    let path = root.join(HINT_USAGE_FILE);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(file, usage)?;
+    Ok(())
+}
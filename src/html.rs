@@ -0,0 +1,7 @@
+// 转义 HTML 特殊字符，用于网页界面和相似度报告
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
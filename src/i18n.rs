@@ -0,0 +1,381 @@
+use std::fmt;
+
+// 命令行输出使用的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    Zh,
+}
+
+impl Lang {
+    // 解析 en、zh、zh_CN.UTF-8 等形式的语言标识
+    pub fn parse(value: &str) -> Option<Lang> {
+        let code = value
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match code.as_str() {
+            "en" | "c" | "posix" => Some(Lang::En),
+            "zh" => Some(Lang::Zh),
+            _ => None,
+        }
+    }
+
+    // 从命令行参数中取出 --lang <code> 或 --lang=<code>，未指定时读取环境变量 LANG
+    // 返回的第二项为无法识别的语言标识
+    pub fn from_args(args: &mut Vec<String>) -> (Lang, Option<String>) {
+        let mut requested = None;
+        let mut i = 0;
+        while i < args.len() {
+            if args[i] == "--lang" {
+                args.remove(i);
+                if i < args.len() {
+                    requested = Some(args.remove(i));
+                }
+            } else if let Some(code) = args[i].strip_prefix("--lang=") {
+                requested = Some(code.to_string());
+                args.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        match requested {
+            Some(code) => match Lang::parse(&code) {
+                Some(lang) => (lang, None),
+                None => (Lang::En, Some(code)),
+            },
+            None => (
+                std::env::var("LANG")
+                    .ok()
+                    .and_then(|value| Lang::parse(&value))
+                    .unwrap_or_default(),
+                None,
+            ),
+        }
+    }
+}
+
+// 按语言查找命令行输出文本，缺少的条目回退到英文
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Messages {
+    lang: Lang,
+}
+
+impl Messages {
+    pub fn new(lang: Lang) -> Self {
+        Self { lang }
+    }
+
+    pub fn lang(&self) -> Lang {
+        self.lang
+    }
+
+    // 查找文本；英文中也没有的条目原样返回键名
+    pub fn text<'a>(&self, key: &'a str) -> &'a str {
+        lookup(catalog(self.lang), key)
+            .or_else(|| lookup(EN, key))
+            .unwrap_or(key)
+    }
+
+    // 查找文本并把 {0}、{1} 等占位符替换为对应参数；只扫描一遍模板，参数中的占位符不会再被替换
+    pub fn format(&self, key: &str, args: &[&dyn fmt::Display]) -> String {
+        let template = self.text(key);
+        let mut text = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let placeholder = after.find('}').and_then(|end| {
+                let index = after[..end].parse::<usize>().ok()?;
+                Some((end, args.get(index)?))
+            });
+            match placeholder {
+                Some((end, arg)) => {
+                    text.push_str(&arg.to_string());
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    text.push('{');
+                    rest = after;
+                }
+            }
+        }
+        text.push_str(rest);
+        text
+    }
+
+    // 当前语言中尚未翻译的条目
+    pub fn missing(&self) -> Vec<&'static str> {
+        let catalog = catalog(self.lang);
+        EN.iter()
+            .map(|(key, _)| *key)
+            .filter(|key| lookup(catalog, key).is_none())
+            .collect()
+    }
+}
+
+fn catalog(lang: Lang) -> &'static [(&'static str, &'static str)] {
+    match lang {
+        Lang::En => EN,
+        Lang::Zh => ZH,
+    }
+}
+
+fn lookup(catalog: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    catalog.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+}
+
+const EN: &[(&str, &str)] = &[
+    ("command.missing", "Please provide a command: 'watch', 'all', 'reset', 'hint', 'list', 'logs', 'verify-report', 'serve', 'similarity', 'grade-batch', 'vendor' or 'preflight'"),
+    ("lang.unknown", "Unknown language {0}, using English"),
+    ("config.load_failed", "Failed to load config file: {0}"),
+    ("templates.failed", "Failed to record exercise templates: {0}"),
+    ("progress.evaluating", "Evaluating {0}: {1}"),
+    ("watch.legend", "[s/Enter] skip to next  [r] rerun  [h] hint  [o] last failure output  [l] list remaining  [q] quit and save"),
+    ("watch.no_failure", "No failed exercise in this session yet."),
+    ("watch.failure", "Output of the last failure ({0}):"),
+    ("watch.remaining", "Remaining exercises ({0}):"),
+    ("session.load_failed", "Failed to load saved progress: {0}"),
+    ("session.save_failed", "Failed to save progress: {0}"),
+    ("session.resumed", "{0}: passed last run and unchanged since, skipping"),
+    ("watch.locked", "{0}: locked until {1} pass"),
+    ("list.failed", "Listing exercises failed: {0}"),
+    ("list.available", "Exercises:"),
+    ("list.locked", "Locked exercises:"),
+    ("list.requires", "requires {0}"),
+    ("status.pending", "not run"),
+    ("status.passed", "PASSED"),
+    ("status.failed", "FAILED"),
+    ("summary.title", "Summary:"),
+    ("summary.total_exercises", "Total exercises: {0}"),
+    ("summary.total_successes", "Total successes: {0}"),
+    ("summary.total_failures", "Total failures: {0}"),
+    ("summary.total_score", "Total score: {0}"),
+    ("summary.total_flaky", "Flaky (passed after retry): {0}"),
+    ("summary.flaky_exercise", "{0}: passed on attempt {1}"),
+    ("summary.minimum_not_met", " (minimum not met)"),
+    ("report.signed", "Report signed with key {0}"),
+    ("report.sign_failed", "Error signing report: {0}"),
+    ("report.key_failed", "Error reading signing key: {0}"),
+    ("report.save_failed", "Error saving report: {0}"),
+    ("reset.failed", "Reset failed: {0}"),
+    ("reset.no_template", "No pristine template for {0}: it is not in the git history"),
+    ("reset.nothing", "Nothing to reset."),
+    ("reset.confirm", "Discard the changes above in {0} exercise(s)? [y/N]"),
+    ("reset.cancelled", "Reset cancelled."),
+    ("reset.done", "{0}: RESET"),
+    ("hint.failed", "Hint failed: {0}"),
+    ("hint.none", "No hints available for {0}."),
+    ("hint.line", "Hint {0}/{1}: {2}"),
+    ("hint.all_revealed", "All hints for {0} have been revealed."),
+    ("verify.failed", "Verification failed: {0}"),
+    ("verify.no_key", "no signing key configured"),
+    ("verify.other_key", "Report was signed with a different key ({0})"),
+    ("verify.signature_valid", "Signature: valid"),
+    ("verify.signature_invalid", "Signature: INVALID, the report was modified after signing"),
+    ("verify.sources_match", "Sources: match the working tree"),
+    ("verify.sources_changed", "Sources changed since signing:"),
+    ("toolchain.too_old", "Refusing to grade: {0}"),
+    ("logs.failed", "Showing logs failed: {0}"),
+    ("logs.none", "No logs recorded for {0}."),
+    ("logs.run", "Latest logs for {0} (run {1}):"),
+    ("logs.step", "{0}: {1} (exit {2}, {3} ms)"),
+    ("serve.listening", "Serving results on http://{0}"),
+    ("serve.failed", "Server failed: {0}"),
+    ("serve.accept_failed", "Failed to accept connection: {0}"),
+    ("similarity.failed", "Similarity check failed: {0}"),
+    ("similarity.exercise", "{0}: {1} similar pair(s) among {2} submission(s)"),
+    ("similarity.written", "Similarity report written to {0}"),
+    ("batch.failed", "Batch grading failed: {0}"),
+    ("batch.none", "No student repositories found in {0}"),
+    ("batch.unsandboxed", "WARNING: grading without the sandbox. Student code runs with your permissions and can read or change other students' workspaces and your files. Pass --sandbox to isolate it."),
+    ("batch.graded", "{0}: score {1}, {2}/{3} passed"),
+    ("batch.error", "{0}: {1}"),
+    ("batch.written", "Gradebook written to {0} and {1}"),
+    ("vendor.failed", "Vendoring failed: {0}"),
+    ("vendor.done", "Vendored {0} crate(s) into {1}; offline builds configured in {2}"),
+    ("preflight.failed", "Pre-flight check failed: {0}"),
+    ("preflight.none", "No Cargo exercises to check"),
+    ("preflight.ok", "{0}: builds offline"),
+    ("preflight.issue", "{0}: cannot build offline: {1}"),
+    ("preflight.summary", "{0} exercise(s) cannot build offline; run 'vendor' on a machine with network access and commit the vendored sources"),
+    ("preflight.passed", "All {0} Cargo exercise(s) can build offline"),
+    ("eval.unknown_type", "Unknown exercise type: {0}"),
+    ("eval.sandbox_unavailable", "{0}: sandbox unavailable: {1}"),
+    ("eval.cpu_exceeded", "{0}: CPU time {1} ms exceeds budget {2} ms"),
+    ("eval.cpu_within", "{0}: CPU time {1} ms within budget {2} ms"),
+    ("eval.retrying", "{0}: retrying (attempt {1}/{2})"),
+    ("eval.timed_out", "{0}: evaluation exceeded the {1} s time limit and was stopped"),
+    ("eval.flaky", "{0}: FLAKY, passed after {1} attempts"),
+    ("eval.sandbox_violation", "{0}: SANDBOX VIOLATION in {1}"),
+    ("eval.test_passed", "{0}: TEST PASSED"),
+    ("eval.test_failed", "{0}: TEST FAILED"),
+    ("eval.test_error", "Error running test executable for {0}: {1}"),
+    ("eval.compile_failed", "{0}: COMPILATION FAILED"),
+    ("eval.rustc_error", "Error executing rustc --test for {0}"),
+    ("eval.binary_removed", "Successfully removed test binary: {0}"),
+    ("eval.binary_remove_failed", "Failed to remove test binary {0}: {1}"),
+    ("eval.target_cleaned", "Successfully cleaned up target directory in: {0}"),
+    ("eval.target_clean_failed", "Failed to clean up target directory: {0}"),
+    ("eval.passed", "{0}: PASSED"),
+    ("eval.failed", "{0}: FAILED"),
+    ("eval.doc_passed", "{0}: DOC TESTS PASSED"),
+    ("eval.doc_failed", "{0}: DOC TESTS FAILED"),
+    ("eval.script_error", "Error running {0}: {1}"),
+    ("eval.build_failed", "{0}: BUILD FAILED"),
+    ("eval.no_cases", "No input files found for {0}"),
+    ("eval.cases_error", "Failed to read cases for {0}: {1}"),
+    ("eval.missing_output", "Missing expected output {0}"),
+    ("eval.case_passed", "{0}: case {1} PASSED"),
+    ("eval.case_failed", "{0}: case {1} FAILED"),
+    ("eval.case_error", "Error running {0} on {1}: {2}"),
+    ("eval.constraints_passed", "{0}: CONSTRAINTS PASSED"),
+    ("eval.constraints_error", "Constraint check failed for {0}: {1}"),
+    ("eval.leaked", "{0}: {1} leaked {2} allocation(s), {3} bytes"),
+    ("eval.leak_skipped", "{0}: leak check skipped, the exercise declares its own #[global_allocator]"),
+    ("eval.leak_error", "Leak check failed for {0}: {1}"),
+    ("eval.differential_passed", "{0}: {1} differential cases PASSED"),
+    ("eval.counterexample", "{0}: counterexample at case {1} (seed {2})"),
+    ("eval.counterexample_input", "  input:    {0}"),
+    ("eval.counterexample_expected", "  expected: {0}"),
+    ("eval.counterexample_actual", "  actual:   {0}"),
+    ("eval.counterexample_seed", "  reproduce with CARGOTEST_SEED={0}"),
+    ("eval.differential_error", "Differential test failed for {0}: {1}"),
+    ("eval.complexity_sample", "  n = {0}: {1} ns"),
+    ("eval.complexity_timeout", "  n = {0}: exceeded {1} ms"),
+    ("eval.complexity_within", "{0}: estimated {1} within target {2}"),
+    ("eval.complexity_exceeds", "{0}: estimated {1} exceeds target {2}"),
+    ("eval.complexity_too_few", "Complexity check for {0} measured too few sizes"),
+    ("eval.complexity_error", "Complexity check failed for {0}: {1}"),
+    ("exercise.unknown", "Unknown exercise: {0}"),
+    ("usage", "Usage: {0}"),
+];
+
+const ZH: &[(&str, &str)] = &[
+    ("command.missing", "请提供命令：'watch'、'all'、'reset'、'hint'、'list'、'logs'、'verify-report'、'serve'、'similarity'、'grade-batch'、'vendor' 或 'preflight'"),
+    ("lang.unknown", "无法识别的语言 {0}，使用英文输出"),
+    ("config.load_failed", "加载配置文件失败：{0}"),
+    ("templates.failed", "记录习题模板失败：{0}"),
+    ("progress.evaluating", "正在评测 {0}：{1}"),
+    ("watch.legend", "[s/回车] 跳到下一题  [r] 重新评测  [h] 提示  [o] 最近一次失败的输出  [l] 剩余习题  [q] 保存并退出"),
+    ("watch.no_failure", "本次还没有未通过的习题。"),
+    ("watch.failure", "最近一次未通过的评测（{0}）的输出："),
+    ("watch.remaining", "剩余习题（{0} 道）："),
+    ("session.load_failed", "读取评测进度失败：{0}"),
+    ("session.save_failed", "保存评测进度失败：{0}"),
+    ("session.resumed", "{0}：上次已通过且之后未修改，跳过"),
+    ("watch.locked", "{0}：需先通过 {1}，暂不评测"),
+    ("list.failed", "列出习题失败：{0}"),
+    ("list.available", "习题："),
+    ("list.locked", "未解锁的习题："),
+    ("list.requires", "需先通过 {0}"),
+    ("status.pending", "未评测"),
+    ("status.passed", "通过"),
+    ("status.failed", "未通过"),
+    ("summary.title", "评测结果："),
+    ("summary.total_exercises", "习题总数：{0}"),
+    ("summary.total_successes", "通过：{0}"),
+    ("summary.total_failures", "未通过：{0}"),
+    ("summary.total_score", "总分：{0}"),
+    ("summary.total_flaky", "重试后才通过（不稳定）：{0}"),
+    ("summary.flaky_exercise", "{0}：第 {1} 次才通过"),
+    ("summary.minimum_not_met", "（未达到最低通过数）"),
+    ("report.signed", "报告已使用密钥 {0} 签名"),
+    ("report.sign_failed", "报告签名失败：{0}"),
+    ("report.key_failed", "读取签名密钥失败：{0}"),
+    ("report.save_failed", "保存报告失败：{0}"),
+    ("reset.failed", "重置失败：{0}"),
+    ("reset.no_template", "git 历史中没有 {0}，无法取得初始模板"),
+    ("reset.nothing", "没有需要重置的修改。"),
+    ("reset.confirm", "放弃以上 {0} 道习题中的修改？[y/N]"),
+    ("reset.cancelled", "已取消重置。"),
+    ("reset.done", "{0}：已重置"),
+    ("hint.failed", "获取提示失败：{0}"),
+    ("hint.none", "{0} 没有提示。"),
+    ("hint.line", "提示 {0}/{1}：{2}"),
+    ("hint.all_revealed", "{0} 的提示已全部显示。"),
+    ("verify.failed", "校验失败：{0}"),
+    ("verify.no_key", "未配置签名密钥"),
+    ("verify.other_key", "报告由其他密钥（{0}）签名"),
+    ("verify.signature_valid", "签名：有效"),
+    ("verify.signature_invalid", "签名：无效，报告在签名后被修改"),
+    ("verify.sources_match", "源码：与工作区一致"),
+    ("verify.sources_changed", "签名后发生变化的源码："),
+    ("toolchain.too_old", "拒绝评测：{0}"),
+    ("logs.failed", "显示日志失败：{0}"),
+    ("logs.none", "{0} 没有评测日志。"),
+    ("logs.run", "{0} 最近一次评测的日志（运行 {1}）："),
+    ("logs.step", "{0}：{1}（退出码 {2}，{3} 毫秒）"),
+    ("serve.listening", "结果页面：http://{0}"),
+    ("serve.failed", "服务器出错：{0}"),
+    ("serve.accept_failed", "接受连接失败：{0}"),
+    ("similarity.failed", "相似度检测失败：{0}"),
+    ("similarity.exercise", "{0}：{2} 份提交中有 {1} 对相似"),
+    ("similarity.written", "相似度报告已写入 {0}"),
+    ("batch.failed", "批量评测失败：{0}"),
+    ("batch.none", "{0} 中没有学生仓库"),
+    ("batch.unsandboxed", "警告：未启用沙箱。学生代码以当前用户的权限运行，可以读取或修改其他学生的工作目录和你的文件。使用 --sandbox 隔离学生代码。"),
+    ("batch.graded", "{0}：{1} 分，通过 {2}/{3}"),
+    ("batch.error", "{0}：{1}"),
+    ("batch.written", "成绩册已写入 {0} 和 {1}"),
+    ("vendor.failed", "下载依赖源码失败：{0}"),
+    ("vendor.done", "已将 {0} 个包的源码下载到 {1}，离线构建配置已写入 {2}"),
+    ("preflight.failed", "离线构建检查失败：{0}"),
+    ("preflight.none", "没有需要检查的 Cargo 习题"),
+    ("preflight.ok", "{0}：可以离线构建"),
+    ("preflight.issue", "{0}：无法离线构建：{1}"),
+    ("preflight.summary", "{0} 道习题无法离线构建；请在有网络的机器上运行 'vendor' 并提交下载的源码"),
+    ("preflight.passed", "全部 {0} 道 Cargo 习题都可以离线构建"),
+    ("eval.unknown_type", "未知的习题类型：{0}"),
+    ("eval.sandbox_unavailable", "{0}：无法建立沙箱：{1}"),
+    ("eval.cpu_exceeded", "{0}：CPU 时间 {1} 毫秒，超出预算 {2} 毫秒"),
+    ("eval.cpu_within", "{0}：CPU 时间 {1} 毫秒，未超出预算 {2} 毫秒"),
+    ("eval.retrying", "{0}：重试（第 {1}/{2} 次）"),
+    ("eval.timed_out", "{0}：评测超过 {1} 秒的时间上限，已终止"),
+    ("eval.flaky", "{0}：不稳定，第 {1} 次才通过"),
+    ("eval.sandbox_violation", "{0}：{1} 违反沙箱限制"),
+    ("eval.test_passed", "{0}：测试通过"),
+    ("eval.test_failed", "{0}：测试未通过"),
+    ("eval.test_error", "运行 {0} 的测试程序出错：{1}"),
+    ("eval.compile_failed", "{0}：编译失败"),
+    ("eval.rustc_error", "无法对 {0} 运行 rustc --test"),
+    ("eval.binary_removed", "已删除测试程序：{0}"),
+    ("eval.binary_remove_failed", "删除测试程序 {0} 失败：{1}"),
+    ("eval.target_cleaned", "已清理 {0} 下的 target 目录"),
+    ("eval.target_clean_failed", "清理 target 目录失败：{0}"),
+    ("eval.passed", "{0}：通过"),
+    ("eval.failed", "{0}：未通过"),
+    ("eval.doc_passed", "{0}：文档测试通过"),
+    ("eval.doc_failed", "{0}：文档测试未通过"),
+    ("eval.script_error", "运行 {0} 出错：{1}"),
+    ("eval.build_failed", "{0}：构建失败"),
+    ("eval.no_cases", "{0} 中没有输入文件"),
+    ("eval.cases_error", "读取 {0} 的用例失败：{1}"),
+    ("eval.missing_output", "缺少期望输出 {0}"),
+    ("eval.case_passed", "{0}：用例 {1} 通过"),
+    ("eval.case_failed", "{0}：用例 {1} 未通过"),
+    ("eval.case_error", "在用例 {1} 上运行 {0} 出错：{2}"),
+    ("eval.constraints_passed", "{0}：满足代码约束"),
+    ("eval.constraints_error", "{0} 的代码约束检查失败：{1}"),
+    ("eval.leaked", "{0}：{1} 泄漏了 {2} 次分配，共 {3} 字节"),
+    ("eval.leak_skipped", "{0}：习题自行声明了 #[global_allocator]，跳过内存泄漏检查"),
+    ("eval.leak_error", "{0} 的内存泄漏检查失败：{1}"),
+    ("eval.differential_passed", "{0}：{1} 个差分测试用例通过"),
+    ("eval.counterexample", "{0}：第 {1} 个用例发现反例（种子 {2}）"),
+    ("eval.counterexample_input", "  输入：    {0}"),
+    ("eval.counterexample_expected", "  期望：    {0}"),
+    ("eval.counterexample_actual", "  实际：    {0}"),
+    ("eval.counterexample_seed", "  设置 CARGOTEST_SEED={0} 可复现"),
+    ("eval.differential_error", "{0} 的差分测试失败：{1}"),
+    ("eval.complexity_sample", "  n = {0}：{1} 纳秒"),
+    ("eval.complexity_timeout", "  n = {0}：超过 {1} 毫秒"),
+    ("eval.complexity_within", "{0}：估计为 {1}，符合要求 {2}"),
+    ("eval.complexity_exceeds", "{0}：估计为 {1}，超过要求 {2}"),
+    ("eval.complexity_too_few", "{0} 的复杂度检查测得的规模过少"),
+    ("eval.complexity_error", "{0} 的复杂度检查失败：{1}"),
+    ("exercise.unknown", "未知习题：{0}"),
+    ("usage", "用法：{0}"),
+];
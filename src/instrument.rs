@@ -0,0 +1,102 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::sandbox::{self, Sandbox};
+
+// 插桩后的测试程序存放目录，相对于仓库根目录
+const INSTRUMENT_DIR: &str = ".cargotest/instrumented";
+
+// 驱动程序中供输入生成器使用的随机数生成器，以 super::Rng 引用
+pub(crate) const RNG: &str = r#"
+    // SplitMix64，保证同一种子生成相同的输入序列
+    #[allow(dead_code)]
+    pub struct Rng(u64);
+
+    #[allow(dead_code)]
+    impl Rng {
+        pub fn new(seed: u64) -> Self {
+            Rng(seed)
+        }
+
+        pub fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        }
+
+        // [0, n)
+        pub fn below(&mut self, n: u64) -> u64 {
+            if n == 0 { 0 } else { self.next_u64() % n }
+        }
+
+        // [lo, hi)
+        pub fn range(&mut self, lo: i32, hi: i32) -> i32 {
+            lo + self.below((hi as i64 - lo as i64) as u64) as i32
+        }
+
+        pub fn chance(&mut self, percent: u64) -> bool {
+            self.below(100) < percent
+        }
+    }
+"#;
+
+// 在习题源码末尾追加额外代码后编译出的测试程序，离开作用域时删除
+pub(crate) struct InstrumentedBinary {
+    source: PathBuf,
+    pub binary: PathBuf,
+}
+
+impl Drop for InstrumentedBinary {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.source);
+        let _ = fs::remove_file(&self.binary);
+    }
+}
+
+// 将 extra 追加到单文件习题之后，使用 rustc --test 编译；inputs 为 extra 引用的文件。
+// 启用沙箱时编译同样在沙箱中进行，只能读取 inputs、写入插桩目录
+pub(crate) fn build_single_file(
+    root: &Path,
+    file_path: &Path,
+    extra: &str,
+    tag: &str,
+    inputs: &[PathBuf],
+    sandbox: Option<&Sandbox>,
+) -> io::Result<InstrumentedBinary> {
+    let stem = file_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let work_dir = root.join(INSTRUMENT_DIR);
+    fs::create_dir_all(&work_dir)?;
+
+    let instrumented = InstrumentedBinary {
+        source: work_dir.join(format!("{}-{}.rs", stem, tag)),
+        binary: work_dir.join(format!("{}-{}", stem, tag)),
+    };
+
+    let mut code = fs::read_to_string(file_path)?;
+    code.push_str(extra);
+    fs::write(&instrumented.source, code)?;
+
+    let build_sandbox = sandbox
+        .map(|sandbox| sandbox.allowing(inputs, std::slice::from_ref(&work_dir)))
+        .transpose()?;
+    let mut command = Command::new("rustc");
+    command
+        .arg("--test")
+        .arg(&instrumented.source)
+        .arg("-o")
+        .arg(&instrumented.binary);
+    let compile = sandbox::output(&mut command, build_sandbox.as_ref(), None)?.output;
+    if !compile.status.success() {
+        return Err(io::Error::other(format!(
+            "instrumented build failed:\n{}",
+            String::from_utf8_lossy(&compile.stderr)
+        )));
+    }
+    Ok(instrumented)
+}
@@ -0,0 +1,267 @@
+use crate::cpu;
+use crate::evaluator::Log;
+use crate::i18n::Messages;
+use crate::logs::StepLogger;
+use crate::registry::{clean_target_directory, log_outcome};
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 输出比较方式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Compare {
+    #[default]
+    Exact,
+    Trimmed,
+    Float,
+}
+
+// io_program 类型习题的配置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IoSettings {
+    // 存放 *.in / *.out 用例的目录，相对于习题目录
+    #[serde(default = "default_cases_dir")]
+    pub cases_dir: String,
+    #[serde(default)]
+    pub compare: Compare,
+    // float 模式下允许的误差
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    // 每个用例的运行时间上限，超时即终止程序并判为不通过
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Default for IoSettings {
+    fn default() -> Self {
+        Self {
+            cases_dir: default_cases_dir(),
+            compare: Compare::default(),
+            tolerance: default_tolerance(),
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+}
+
+fn default_cases_dir() -> String {
+    "io".to_string()
+}
+
+fn default_tolerance() -> f64 {
+    1e-6
+}
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+// 评测输入输出类习题：构建程序，逐个喂入 *.in 并与 *.out 比较
+pub fn evaluate_io_program(
+    proj_path: &Path,
+    settings: &IoSettings,
+    msg: Messages,
+    log: &mut Log,
+    steps: &mut StepLogger,
+) -> bool {
+    let cases = match collect_cases(&proj_path.join(&settings.cases_dir), msg, log) {
+        Ok(cases) if !cases.is_empty() => cases,
+        Ok(_) => {
+            log(msg.format("eval.no_cases", &[&proj_path.display()]));
+            return false;
+        }
+        Err(e) => {
+            log(msg.format("eval.cases_error", &[&proj_path.display(), &e]));
+            return false;
+        }
+    };
+
+    // 直接运行构建出的程序，cargo 的启动和新鲜度检查不计入各用例的 CPU 时间
+    let program = steps
+        .compile(
+            "build",
+            Command::new("cargo")
+                .args(["build", "--message-format=json-render-diagnostics"])
+                .current_dir(proj_path),
+        )
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| built_executable(&out.stdout));
+    let Some(program) = program else {
+        let line = msg.format("eval.build_failed", &[&proj_path.display()]);
+        log(format!("\x1b[31m{}\x1b[0m", line));
+        clean_target_directory(proj_path, msg, log);
+        return false;
+    };
+
+    let mut passed = true;
+    for case in &cases {
+        if !run_case(proj_path, &program, case, settings, msg, log, steps) {
+            passed = false;
+        }
+    }
+
+    log_outcome(passed, &proj_path.display(), msg, log);
+
+    clean_target_directory(proj_path, msg, log);
+
+    passed
+}
+
+// 找出目录下所有带有对应 .out 文件的 .in 文件
+fn collect_cases(
+    cases_dir: &Path,
+    msg: Messages,
+    log: &mut Log,
+) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+    let mut cases = Vec::new();
+    for entry in fs::read_dir(cases_dir)? {
+        let input = entry?.path();
+        if input.extension().is_some_and(|ext| ext == "in") {
+            let expected = input.with_extension("out");
+            if expected.exists() {
+                cases.push((input, expected));
+            } else {
+                log(msg.format("eval.missing_output", &[&expected.display()]));
+            }
+        }
+    }
+    cases.sort();
+    Ok(cases)
+}
+
+// cargo build 的 JSON 消息中构建出的二进制程序
+fn built_executable(messages: &[u8]) -> Option<PathBuf> {
+    String::from_utf8_lossy(messages)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .filter(|message| {
+            message["target"]["kind"]
+                .as_array()
+                .is_some_and(|kinds| kinds.iter().any(|kind| kind == "bin"))
+        })
+        .find_map(|message| message["executable"].as_str().map(PathBuf::from))
+}
+
+fn run_case(
+    proj_path: &Path,
+    program: &Path,
+    (input, expected): &(PathBuf, PathBuf),
+    settings: &IoSettings,
+    msg: Messages,
+    log: &mut Log,
+    steps: &mut StepLogger,
+) -> bool {
+    let case_name = input.file_stem().unwrap_or_default().to_string_lossy();
+    let step = format!("case {}", case_name);
+    let actual = match run_program(proj_path, program, input, settings, &step, steps) {
+        Ok(actual) => actual,
+        Err(e) => {
+            log(msg.format("eval.case_error", &[&proj_path.display(), &case_name, &e]));
+            return false;
+        }
+    };
+    let expected_text = fs::read_to_string(expected).unwrap_or_default();
+
+    if outputs_match(&actual, &expected_text, settings) {
+        let line = msg.format("eval.case_passed", &[&proj_path.display(), &case_name]);
+        log(format!("\x1b[32m{}\x1b[0m", line));
+        return true;
+    }
+
+    let line = msg.format("eval.case_failed", &[&proj_path.display(), &case_name]);
+    log(format!("\x1b[31m{}\x1b[0m", line));
+    let diff = TextDiff::from_lines(&expected_text, &actual)
+        .unified_diff()
+        .header(&expected.display().to_string(), "actual")
+        .to_string();
+    for line in diff.lines() {
+        log(line.to_string());
+    }
+    false
+}
+
+fn run_program(
+    proj_path: &Path,
+    program: &Path,
+    input: &Path,
+    settings: &IoSettings,
+    step: &str,
+    steps: &mut StepLogger,
+) -> io::Result<String> {
+    let stdin_data = fs::read(input)?;
+    let start_time = Instant::now();
+    let mut command = Command::new(program);
+    command
+        .current_dir(proj_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    cpu::own_process_group(&mut command);
+    steps.confine(&mut command)?;
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // 程序可能不读取标准输入，在单独的线程中写入以免阻塞；写入失败不视为错误
+        thread::spawn(move || {
+            let _ = stdin.write_all(&stdin_data);
+        });
+    }
+    // 单个用例的时间限制不超过整道习题剩余的时间
+    let case_timeout = Duration::from_millis(settings.timeout_ms);
+    let remaining = steps.remaining().filter(|remaining| *remaining < case_timeout);
+    let finished = cpu::wait_with_output(child, Some(remaining.unwrap_or(case_timeout)))?;
+    let output = finished.output;
+    steps.record(step, &command, &output, start_time.elapsed(), finished.cpu_time);
+    if finished.timed_out && remaining.is_some() {
+        return Err(steps.timed_out());
+    }
+    if finished.timed_out {
+        return Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("timed out after {} ms", settings.timeout_ms),
+        ));
+    }
+    if !output.status.success() {
+        return Err(io::Error::other(format!("exited with {}", output.status)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// 按 settings.compare 比较程序输出与期望输出
+pub fn outputs_match(actual: &str, expected: &str, settings: &IoSettings) -> bool {
+    match settings.compare {
+        Compare::Exact => actual == expected,
+        Compare::Trimmed => trimmed_lines(actual) == trimmed_lines(expected),
+        Compare::Float => {
+            let actual: Vec<&str> = actual.split_whitespace().collect();
+            let expected: Vec<&str> = expected.split_whitespace().collect();
+            actual.len() == expected.len()
+                && actual
+                    .iter()
+                    .zip(&expected)
+                    .all(|(a, e)| tokens_match(a, e, settings.tolerance))
+        }
+    }
+}
+
+// 去掉每行首尾空白以及首尾空行
+fn trimmed_lines(text: &str) -> Vec<&str> {
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let start = lines.iter().position(|l| !l.is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !l.is_empty()).map_or(start, |i| i + 1);
+    lines[start..end].to_vec()
+}
+
+// 数值按相对误差比较，其他内容按字面比较
+fn tokens_match(actual: &str, expected: &str, tolerance: f64) -> bool {
+    match (actual.parse::<f64>(), expected.parse::<f64>()) {
+        (Ok(a), Ok(e)) => (a - e).abs() <= tolerance * e.abs().max(1.0),
+        _ => actual == expected,
+    }
+}
@@ -0,0 +1,195 @@
+use crate::instrument;
+use crate::sandbox::{self, Sandbox};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Output};
+use std::time::Instant;
+use syn::visit::Visit;
+
+// 插桩程序在退出时输出统计信息所用的前缀
+const REPORT_MARKER: &str = "__CARGOTEST_LEAK_CHECK__";
+
+// 作为对照的空测试，用于扣除测试框架自身未释放的内存
+const BASELINE_TEST: &str = "__cargotest_leak_check::__cargotest_leak_baseline";
+
+// 追加到习题源码末尾的计数分配器
+const INSTRUMENTATION: &str = r#"
+mod __cargotest_leak_check {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
+
+    static ALLOCATIONS: AtomicIsize = AtomicIsize::new(0);
+    static BYTES: AtomicIsize = AtomicIsize::new(0);
+    static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" {
+        fn atexit(callback: extern "C" fn()) -> i32;
+    }
+
+    extern "C" fn report() {
+        let allocations = ALLOCATIONS.load(Ordering::SeqCst);
+        let bytes = BYTES.load(Ordering::SeqCst);
+        eprintln!("__CARGOTEST_LEAK_CHECK__ {} {}", allocations, bytes);
+    }
+
+    struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            if !REGISTERED.swap(true, Ordering::SeqCst) {
+                atexit(report);
+            }
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+                BYTES.fetch_add(layout.size() as isize, Ordering::SeqCst);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            ALLOCATIONS.fetch_sub(1, Ordering::SeqCst);
+            BYTES.fetch_sub(layout.size() as isize, Ordering::SeqCst);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                BYTES.fetch_add(new_size as isize - layout.size() as isize, Ordering::SeqCst);
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn __cargotest_leak_baseline() {}
+}
+"#;
+
+// 内存泄漏检查的处理方式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LeakCheck {
+    #[default]
+    Off,
+    Warn,
+    Fail,
+}
+
+// 单个测试结束后未释放的内存
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TestLeak {
+    pub test: String,
+    pub allocations: isize,
+    pub bytes: isize,
+}
+
+// 使用计数分配器重新编译单文件习题，逐个运行测试并与空测试对照
+// 启用沙箱时测试程序在沙箱中运行，超过 deadline 即终止；习题自行声明了 #[global_allocator] 时无法插桩，返回 None
+pub fn check_single_file(
+    root: &Path,
+    file_path: &Path,
+    sandbox: Option<&Sandbox>,
+    deadline: Option<Instant>,
+) -> io::Result<Option<Vec<TestLeak>>> {
+    if declares_global_allocator(&fs::read_to_string(file_path)?) {
+        return Ok(None);
+    }
+    let instrumented =
+        instrument::build_single_file(root, file_path, INSTRUMENTATION, "leak", &[], sandbox)?;
+    let binary = &instrumented.binary;
+
+    let baseline = measure_test(binary, BASELINE_TEST, sandbox, deadline)?;
+    let mut leaks = Vec::new();
+    for test in list_tests(binary, sandbox, deadline)? {
+        if test == BASELINE_TEST {
+            continue;
+        }
+        let (allocations, bytes) = measure_test(binary, &test, sandbox, deadline)?;
+        if allocations > baseline.0 {
+            leaks.push(TestLeak {
+                test,
+                allocations: allocations - baseline.0,
+                bytes: bytes - baseline.1,
+            });
+        }
+    }
+    Ok(Some(leaks))
+}
+
+// 源码中是否有带 #[global_allocator] 的 static，一个程序只能有一个全局分配器
+fn declares_global_allocator(source: &str) -> bool {
+    struct Finder(bool);
+
+    impl<'ast> Visit<'ast> for Finder {
+        fn visit_item_static(&mut self, item: &'ast syn::ItemStatic) {
+            if item.attrs.iter().any(|attr| attr.path().is_ident("global_allocator")) {
+                self.0 = true;
+            }
+        }
+    }
+
+    // 无法解析时按文本判断，编译错误留给测试步骤报告
+    match syn::parse_file(source) {
+        Ok(file) => {
+            let mut finder = Finder(false);
+            finder.visit_file(&file);
+            finder.0
+        }
+        Err(_) => source.contains("global_allocator"),
+    }
+}
+
+fn list_tests(
+    binary: &Path,
+    sandbox: Option<&Sandbox>,
+    deadline: Option<Instant>,
+) -> io::Result<Vec<String>> {
+    let mut command = Command::new(binary);
+    command.args(["--list", "--format", "terse"]);
+    let output = run(&mut command, sandbox, deadline)?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_suffix(": test"))
+        .map(str::to_string)
+        .collect())
+}
+
+// 单独运行一个测试，返回进程退出时仍未释放的分配次数和字节数
+fn measure_test(
+    binary: &Path,
+    test: &str,
+    sandbox: Option<&Sandbox>,
+    deadline: Option<Instant>,
+) -> io::Result<(isize, isize)> {
+    let mut command = Command::new(binary);
+    command.args(["--exact", test, "--test-threads=1"]);
+    let output = run(&mut command, sandbox, deadline)?;
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter_map(|line| line.strip_prefix(REPORT_MARKER))
+        .find_map(|counts| {
+            let mut parts = counts.split_whitespace().map(str::parse::<isize>);
+            match (parts.next(), parts.next()) {
+                (Some(Ok(allocations)), Some(Ok(bytes))) => Some((allocations, bytes)),
+                _ => None,
+            }
+        })
+        .ok_or_else(|| io::Error::other(format!("no allocation report from {}", test)))
+}
+
+// 运行插桩后的测试程序，到达 deadline 时终止并返回错误
+fn run(command: &mut Command, sandbox: Option<&Sandbox>, deadline: Option<Instant>) -> io::Result<Output> {
+    let timeout = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+    let finished = sandbox::output(command, sandbox, timeout)?;
+    if finished.timed_out {
+        return Err(io::Error::new(io::ErrorKind::TimedOut, "leak check timed out"));
+    }
+    Ok(finished.output)
+}
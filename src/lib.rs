@@ -0,0 +1,31 @@
+// 习题评测库：加载配置、评测习题并生成报告，cargotest 命令行程序基于此实现
+pub mod batch;
+pub mod complexity;
+pub mod config;
+pub mod constraints;
+mod cpu;
+pub mod differential;
+pub mod environment;
+pub mod evaluator;
+mod files;
+pub mod hint;
+mod html;
+pub mod i18n;
+mod instrument;
+pub mod io_program;
+pub mod leak;
+pub mod logs;
+pub mod registry;
+pub mod report;
+pub mod reset;
+pub mod sandbox;
+pub mod server;
+pub mod session;
+pub mod signing;
+pub mod similarity;
+pub mod vendor;
+
+pub use config::{CategoryPolicy, ConfigError, Exercise, ExerciseConfig};
+pub use evaluator::{Evaluator, Progress};
+pub use registry::{Context, EvaluatorRegistry, ExerciseEvaluator};
+pub use report::{CategoryStatistics, ExerciseResult, Report, Statistics};
@@ -0,0 +1,269 @@
+use crate::cpu;
+use crate::sandbox::{self, Sandbox};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// 评测日志目录，每次运行一个子目录，其下每道习题一个子目录
+const LOG_DIR: &str = ".cargotest/logs";
+const STEPS_FILE: &str = "steps.json";
+// 保留的运行数（含新开始的一次），更早的运行目录在新运行开始时删除
+pub const KEPT_RUNS: usize = 20;
+
+// 评测中一个步骤（编译、测试、clippy 等）的记录
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StepLog {
+    pub step: String,
+    pub command: String,
+    // 被信号终止时没有退出码
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub duration_ms: u64,
+    // 运行学生代码的步骤所用的 CPU 时间（用户态与内核态之和）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_ms: Option<u64>,
+    // 输出文件相对于仓库根目录的路径
+    pub stdout: String,
+    pub stderr: String,
+    // 程序违反沙箱限制而被终止的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub violation: Option<String>,
+}
+
+// 保存一道习题各步骤的输出
+pub struct StepLogger {
+    root: PathBuf,
+    dir: PathBuf,
+    steps: Vec<StepLog>,
+    sandbox: Option<Sandbox>,
+    // 整道习题的截止时间及时间限制，超过后正在运行的命令被终止
+    deadline: Option<(Instant, Duration)>,
+}
+
+impl StepLogger {
+    pub(crate) fn new(root: &Path, run_id: &str, exercise: &str) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            dir: Path::new(LOG_DIR).join(run_id).join(exercise),
+            steps: Vec::new(),
+            sandbox: None,
+            deadline: None,
+        }
+    }
+
+    // 启用沙箱后 run_untrusted 运行的程序在沙箱中执行
+    pub(crate) fn sandboxed(mut self, sandbox: Option<Sandbox>) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    pub fn sandbox(&self) -> Option<&Sandbox> {
+        self.sandbox.as_ref()
+    }
+
+    // 限制整道习题的评测时间，从现在开始计算
+    pub(crate) fn time_limit(mut self, limit: Option<Duration>) -> Self {
+        self.deadline = limit.map(|limit| (Instant::now() + limit, limit));
+        self
+    }
+
+    // 整道习题的截止时间，没有时间限制时为 None
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.deadline.map(|(deadline, _)| deadline)
+    }
+
+    // 距截止时间的剩余时间，没有时间限制时为 None
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|(deadline, _)| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    // 超过整道习题的时间限制
+    pub(crate) fn timed_out(&self) -> io::Error {
+        let limit = self.deadline.map_or(Duration::ZERO, |(_, limit)| limit);
+        io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("exercise timed out after {} s", limit.as_secs()),
+        )
+    }
+
+    // 运行可信的命令并记录其输出、退出状态和耗时
+    pub fn run(&mut self, step: &str, command: &mut Command) -> io::Result<Output> {
+        self.execute(step, command, false, false)
+    }
+
+    // 编译学生的代码，启用沙箱时在沙箱中运行：构建脚本和过程宏在编译时就会执行任意代码。
+    // 编译的 CPU 时间不计入题目的 CPU 时间
+    pub fn compile(&mut self, step: &str, command: &mut Command) -> io::Result<Output> {
+        self.execute(step, command, true, false)
+    }
+
+    // 运行不可信的程序（测试程序、学生的程序），启用沙箱时在沙箱中运行，并记录其 CPU 时间
+    pub fn run_untrusted(&mut self, step: &str, command: &mut Command) -> io::Result<Output> {
+        self.execute(step, command, true, true)
+    }
+
+    fn execute(
+        &mut self,
+        step: &str,
+        command: &mut Command,
+        untrusted: bool,
+        measure: bool,
+    ) -> io::Result<Output> {
+        if untrusted {
+            self.confine(command)?;
+        }
+        let start_time = Instant::now();
+        let finished = cpu::output(command, self.remaining()).map_err(|e| self.setup_error(e))?;
+        let cpu_time = finished.cpu_time.filter(|_| measure);
+        self.record(step, command, &finished.output, start_time.elapsed(), cpu_time);
+        if finished.timed_out {
+            return Err(self.timed_out());
+        }
+        Ok(finished.output)
+    }
+
+    // 为自行启动的不可信程序设置沙箱，未启用沙箱时不做任何事
+    pub fn confine(&self, command: &mut Command) -> io::Result<()> {
+        match &self.sandbox {
+            Some(sandbox) => sandbox.apply(command).map_err(|e| self.setup_error(e)),
+            None => Ok(()),
+        }
+    }
+
+    // 启用沙箱时启动失败多半是因为无法建立沙箱
+    fn setup_error(&self, e: io::Error) -> io::Error {
+        if self.sandbox.is_some() {
+            io::Error::new(e.kind(), format!("sandbox setup failed: {}", e))
+        } else {
+            e
+        }
+    }
+
+    // 记录已经运行结束的命令
+    pub fn record(
+        &mut self,
+        step: &str,
+        command: &Command,
+        output: &Output,
+        duration: Duration,
+        cpu_time: Option<Duration>,
+    ) {
+        let file_name = format!("{:02}-{}", self.steps.len() + 1, sanitize(step));
+        let stdout = self.dir.join(format!("{}.stdout", file_name));
+        let stderr = self.dir.join(format!("{}.stderr", file_name));
+        // 日志写入失败不影响评测结果
+        let _ = fs::create_dir_all(self.root.join(&self.dir))
+            .and_then(|_| fs::write(self.root.join(&stdout), &output.stdout))
+            .and_then(|_| fs::write(self.root.join(&stderr), &output.stderr));
+
+        self.steps.push(StepLog {
+            step: step.to_string(),
+            command: describe(command),
+            exit_code: output.status.code(),
+            success: output.status.success(),
+            duration_ms: duration.as_millis() as u64,
+            cpu_ms: cpu_time.map(|time| time.as_millis() as u64),
+            stdout: display_path(&stdout),
+            stderr: display_path(&stderr),
+            violation: self
+                .sandbox
+                .as_ref()
+                .and_then(|_| sandbox::violation(&output.status)),
+        });
+    }
+
+    pub(crate) fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    // 从第 first 个步骤起运行学生代码所用的 CPU 时间之和，没有测量时为 None
+    pub(crate) fn cpu_time_since(&self, first: usize) -> Option<Duration> {
+        self.steps[first..]
+            .iter()
+            .filter_map(|step| step.cpu_ms)
+            .map(Duration::from_millis)
+            .reduce(|a, b| a + b)
+    }
+
+    // 写入步骤清单并返回全部记录
+    pub(crate) fn finish(self) -> Vec<StepLog> {
+        if !self.steps.is_empty() {
+            if let Ok(json) = serde_json::to_string_pretty(&self.steps) {
+                let _ = fs::write(self.root.join(&self.dir).join(STEPS_FILE), json);
+            }
+        }
+        self.steps
+    }
+}
+
+// 以毫秒时间戳作为运行编号，按字典序即按时间排序
+fn new_run_id() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    format!("{:013}", millis)
+}
+
+// 开始一次新的运行：删除最近 KEPT_RUNS - 1 次之前的运行目录，返回新的运行编号
+pub fn start_run(root: &Path) -> String {
+    let runs_dir = root.join(LOG_DIR);
+    if let Ok(runs) = list_runs(&runs_dir) {
+        let stale = runs.len().saturating_sub(KEPT_RUNS - 1);
+        for run_id in &runs[..stale] {
+            let _ = fs::remove_dir_all(runs_dir.join(run_id));
+        }
+    }
+    new_run_id()
+}
+
+// 日志目录下的全部运行编号，按时间从早到晚排序
+fn list_runs(runs_dir: &Path) -> io::Result<Vec<String>> {
+    let mut runs: Vec<String> = fs::read_dir(runs_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    runs.sort();
+    Ok(runs)
+}
+
+// 查找某道习题最近一次运行的日志，返回运行编号和步骤记录
+pub fn latest(root: &Path, exercise: &str) -> io::Result<Option<(String, Vec<StepLog>)>> {
+    let runs_dir = root.join(LOG_DIR);
+    if !runs_dir.exists() {
+        return Ok(None);
+    }
+    for run_id in list_runs(&runs_dir)?.into_iter().rev() {
+        let steps_file = runs_dir.join(&run_id).join(exercise).join(STEPS_FILE);
+        if steps_file.exists() {
+            let steps = serde_json::from_str(&fs::read_to_string(steps_file)?)?;
+            return Ok(Some((run_id, steps)));
+        }
+    }
+    Ok(None)
+}
+
+fn describe(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// 步骤名称用作文件名，只保留字母数字
+fn sanitize(step: &str) -> String {
+    step.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn display_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
@@ -1,258 +1,706 @@
-use serde::{Deserialize, Serialize};
-use std::fs::{self, File};
-use std::path::{Path, PathBuf};
-use std::process::{Command, exit};
-use std::time::Instant;
+use cargotest::config::{find_exercise, Exercise, ExerciseConfig};
+use cargotest::environment::Environment;
+use cargotest::i18n::{Lang, Messages};
+use cargotest::server::Server;
+use cargotest::session::SessionState;
+use cargotest::logs::StepLog;
+use cargotest::{
+    batch, hint, logs, reset, signing, similarity, vendor, Evaluator, ExerciseResult, Progress,
+    Report,
+};
+use std::collections::HashSet;
+use std::fs;
 use std::io::{self, Write};
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Exercise {
-    name: String,
-    path: String,
-    #[serde(rename = "type")]
-    exercise_type: String,  
-    score: i32, 
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-struct ExerciseConfig {
-    easy: Vec<Exercise>,
-    normal: Vec<Exercise>,
-    hard: Vec<Exercise>,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-struct ExerciseResult {
-    name: String,
-    result: bool,
-    score: i32, 
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-struct Statistics {
-    total_exercises: usize,
-    total_successes: usize,
-    total_failures: usize,
-    total_score: i32,  
-    total_time: u64,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-struct Report {
-    exercises: Vec<ExerciseResult>,
-    statistics: Statistics,
-}
+use std::path::Path;
+use std::process::exit;
+use std::time::Instant;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().collect();
+    let (lang, unknown_lang) = Lang::from_args(&mut args);
+    let msg = Messages::new(lang);
+    if let Some(code) = unknown_lang {
+        eprintln!("{}", msg.format("lang.unknown", &[&code]));
+    }
     if args.len() < 2 {
-        eprintln!("Please provide a command: 'watch' or 'all'");
+        eprintln!("{}", msg.text("command.missing"));
         exit(1);
     }
 
     let mode = &args[1];
-    let start_time = Instant::now();
+    let root = Path::new(".");
 
-    
-    let config = match load_exercise_config("exercise_config.json") {
+    let config = match ExerciseConfig::load(root.join("exercise_config.json")) {
         Ok(cfg) => cfg,
         Err(e) => {
-            eprintln!("Failed to load config file: {}", e);
+            eprintln!("{}", msg.format("config.load_failed", &[&e]));
             exit(1);
         }
     };
+    let exercises = config.all_exercises();
+
+    let outcome = match mode.as_str() {
+        "reset" => run_reset(msg, root, &exercises, &args[2..])
+            .map_err(|e| msg.format("reset.failed", &[&e])),
+        "hint" => run_hint(msg, root, &exercises, &args[2..])
+            .map_err(|e| msg.format("hint.failed", &[&e])),
+        "verify-report" => run_verify_report(msg, root, &config, &args[2..])
+            .map_err(|e| msg.format("verify.failed", &[&e])),
+        "list" => run_list(msg, root, &config).map_err(|e| msg.format("list.failed", &[&e])),
+        "logs" => run_logs(msg, root, &exercises, &args[2..])
+            .map_err(|e| msg.format("logs.failed", &[&e])),
+        "serve" => run_server(msg, root, config, &args[2..])
+            .map_err(|e| msg.format("serve.failed", &[&e])),
+        "similarity" => run_similarity(msg, root, &exercises, &args[2..])
+            .map_err(|e| msg.format("similarity.failed", &[&e])),
+        "grade-batch" => run_grade_batch(msg, root, config, &args[2..])
+            .map_err(|e| msg.format("batch.failed", &[&e])),
+        "vendor" => run_vendor(msg, root, &exercises, &args[2..])
+            .map_err(|e| msg.format("vendor.failed", &[&e])),
+        "preflight" => run_preflight(msg, root, &exercises)
+            .map_err(|e| msg.format("preflight.failed", &[&e])),
+        _ => {
+            let resume = mode == "watch" || args[2..].iter().any(|arg| arg == "--resume");
+            run_evaluation(msg, root, mode, resume, &config)
+        }
+    };
+    if let Err(message) = outcome {
+        eprintln!("{}", message);
+        exit(1);
+    }
+}
 
-    let mut report = Report {
-        exercises: Vec::new(),
-        statistics: Statistics {
-            total_exercises: 0,
-            total_successes: 0,
-            total_failures: 0,
-            total_score: 0,
-            total_time: 0,
-        },
+// 评测习题并保存报告，watch 模式下按先修关系排序、跳过未解锁的习题，并在每道题后等待按键
+// resume 时从第一道上次未通过或源码已变化的习题开始，之前的习题沿用上次的结果
+fn run_evaluation(
+    msg: Messages,
+    root: &Path,
+    mode: &str,
+    resume: bool,
+    config: &ExerciseConfig,
+) -> Result<(), String> {
+    let start_time = Instant::now();
+    let mut evaluator =
+        Evaluator::new(root, config)
+            .lang(msg.lang())
+            .on_progress(move |event| print_progress(msg, event));
+    evaluator
+        .check_toolchain()
+        .map_err(|e| msg.format("toolchain.too_old", &[&e]))?;
+    let environment = Environment::capture(root);
+    let mut report = Report::new();
+    report.environment = Some(environment);
+    let mut session = SessionState::load(root, config)
+        .map_err(|e| msg.format("session.load_failed", &[&e]))?;
+
+    let watch = mode == "watch";
+    let exercises = if watch {
+        // 配置加载时已检查循环依赖
+        config.topological_order().unwrap_or_else(|_| config.all_exercises())
+    } else {
+        config.all_exercises()
     };
+    let mut passed = HashSet::new();
+    let mut resuming = resume;
+    let mut last_failure: Option<ExerciseResult> = None;
+
+    'exercises: for (index, exercise) in exercises.iter().enumerate() {
+        if watch {
+            let locked_by = config.locked_by(exercise, &passed);
+            if !locked_by.is_empty() {
+                println!(
+                    "{}",
+                    msg.format("watch.locked", &[&exercise.name, &locked_by.join(", ")])
+                );
+                continue;
+            }
+        }
+
+        // 只跳过上次通过且未修改的题目，遇到第一道未通过或未评测过的题目后不再跳过
+        if resuming {
+            match session.lookup(root, exercise).filter(|saved| saved.result) {
+                Some(saved) => {
+                    println!("{}", msg.format("session.resumed", &[&exercise.name]));
+                    let mut saved = saved.clone();
+                    saved.resumed = true;
+                    passed.insert(exercise.name.clone());
+                    report.record(saved);
+                    continue;
+                }
+                None => resuming = false,
+            }
+        }
+
+        let mut result = evaluator.evaluate(exercise);
+        let mut quit = false;
+        loop {
+            if let Err(e) = session.store(root, exercise, &result) {
+                eprintln!("{}", msg.format("session.save_failed", &[&e]));
+            }
+            if !result.result {
+                last_failure = Some(result.clone());
+            }
+            if !watch {
+                break;
+            }
+
+            println!("\n{}", msg.text("watch.legend"));
+            match read_key() {
+                Some('r') => result = evaluator.evaluate(exercise),
+                Some('h') => {
+                    if let Err(e) = show_next_hint(msg, root, exercise) {
+                        eprintln!("{}", msg.format("hint.failed", &[&e]));
+                    }
+                    continue;
+                }
+                Some('o') => {
+                    show_failure_output(msg, root, last_failure.as_ref());
+                    continue;
+                }
+                Some('l') => {
+                    show_remaining(msg, config, &exercises[index + 1..], &passed, &result);
+                    continue;
+                }
+                Some('s') | Some('\n') | Some(' ') => break,
+                Some('q') | None => {
+                    quit = true;
+                    break;
+                }
+                Some(_) => continue,
+            }
+        }
 
-    
-    evaluate_exercises_from_config(mode, config, &mut report);
+        if result.result {
+            passed.insert(exercise.name.clone());
+        }
+        report.record(result);
+        if quit {
+            break 'exercises;
+        }
+    }
 
-    
-    report.statistics.total_time = start_time.elapsed().as_secs();
-    report.statistics.total_exercises = report.statistics.total_successes + report.statistics.total_failures;
+    report.finish(config, start_time.elapsed());
+
+    let stats = &report.statistics;
+    println!("\n{}", msg.text("summary.title"));
+    println!("{}", msg.format("summary.total_exercises", &[&stats.total_exercises]));
+    println!("{}", msg.format("summary.total_successes", &[&stats.total_successes]));
+    println!("{}", msg.format("summary.total_failures", &[&stats.total_failures]));
+    println!("{}", msg.format("summary.total_score", &[&stats.total_score]));
+    if stats.total_flaky > 0 {
+        println!("{}", msg.format("summary.total_flaky", &[&stats.total_flaky]));
+        for result in report.exercises.iter().filter(|r| r.flaky) {
+            let line = msg.format("summary.flaky_exercise", &[&result.name, &result.attempts.len()]);
+            println!("  \x1b[33m{}\x1b[0m", line);
+        }
+    }
+    for category in &report.categories {
+        let note = if category.requirement_met { "" } else { msg.text("summary.minimum_not_met") };
+        println!(
+            "  {}: {}/{} ({:.1}%){}",
+            category.name, category.score, category.max_score, category.percentage, note
+        );
+    }
 
-    
-    println!("\nSummary:");
-    println!("Total exercises: {}", report.statistics.total_exercises);
-    println!("Total successes: {}", report.statistics.total_successes);
-    println!("Total failures: {}", report.statistics.total_failures);
-    println!("Total score: {}", report.statistics.total_score);
+    match signing::load_key(root) {
+        Ok(Some(key)) => match signing::sign(&mut report, root, config, &key) {
+            Ok(()) => println!("{}", msg.format("report.signed", &[&signing_key_id(&report)])),
+            Err(e) => eprintln!("{}", msg.format("report.sign_failed", &[&e])),
+        },
+        Ok(None) => {}
+        Err(e) => eprintln!("{}", msg.format("report.key_failed", &[&e])),
+    }
 
-    
-    if let Err(e) = save_report_to_json("report.json", &report) {
-        eprintln!("Error saving report: {}", e);
+    if let Err(e) = report.save(root.join("report.json")) {
+        eprintln!("{}", msg.format("report.save_failed", &[&e]));
     }
+    Ok(())
 }
 
+fn signing_key_id(report: &Report) -> &str {
+    report
+        .signature
+        .as_ref()
+        .map(|s| s.key_id.as_str())
+        .unwrap_or_default()
+}
 
-fn load_exercise_config(file_path: &str) -> Result<ExerciseConfig, io::Error> {
-    let file = File::open(file_path)?;
-    let config: ExerciseConfig = serde_json::from_reader(file)?;
-    Ok(config)
+fn print_progress(msg: Messages, event: Progress) {
+    match event {
+        Progress::Started { exercise } => println!(
+            "\n{}",
+            msg.format("progress.evaluating", &[&exercise.exercise_type, &exercise.name])
+        ),
+        Progress::Output { line, .. } => println!("{}", line),
+        Progress::Finished { .. } => {}
+    }
 }
 
+// 处理 reset 命令：reset <name> 或 reset --all
+fn run_reset(msg: Messages, root: &Path, exercises: &[Exercise], args: &[String]) -> io::Result<()> {
+    let targets: Vec<&Exercise> = match args.first().map(String::as_str) {
+        Some("--all") => exercises.iter().collect(),
+        Some(name) => vec![lookup(msg, exercises, name)?],
+        None => return Err(usage(msg, "reset <name> | reset --all")),
+    };
+    snapshot_templates(msg, root, exercises);
+
+    // 先展示将被丢弃的修改
+    let mut changed = Vec::new();
+    for exercise in targets {
+        if !reset::has_template(root, exercise) {
+            eprintln!("{}", msg.format("reset.no_template", &[&exercise.name]));
+            continue;
+        }
+        let diffs = reset::pending_changes(root, exercise)?;
+        if diffs.is_empty() {
+            continue;
+        }
+        for diff in &diffs {
+            print!("{}", diff);
+        }
+        changed.push(exercise);
+    }
+
+    if changed.is_empty() {
+        println!("{}", msg.text("reset.nothing"));
+        return Ok(());
+    }
 
-fn evaluate_exercises_from_config(mode: &str, config: ExerciseConfig, report: &mut Report) {
-    let all_exercises = [config.easy, config.normal, config.hard].concat();
+    if !confirm(&format!("\n{}", msg.format("reset.confirm", &[&changed.len()]))) {
+        println!("{}", msg.text("reset.cancelled"));
+        return Ok(());
+    }
 
-    for exercise in all_exercises {
-        println!("\nEvaluating {}: {}", exercise.exercise_type, exercise.name);
-        let result = evaluate_exercise(&exercise);
+    for exercise in changed {
+        reset::restore(root, exercise)?;
+        println!("\x1b[32m{}\x1b[0m", msg.format("reset.done", &[&exercise.name]));
+    }
+    Ok(())
+}
 
-        let score = if result { exercise.score } else { 0 };
+// 从 git 历史中取出尚未保存的习题模板，失败时只给出警告
+fn snapshot_templates(msg: Messages, root: &Path, exercises: &[Exercise]) {
+    if let Err(e) = reset::snapshot_templates(root, exercises) {
+        eprintln!("{}", msg.format("templates.failed", &[&e]));
+    }
+}
 
-        report.exercises.push(ExerciseResult {
-            name: exercise.name.clone(),
-            result,
-            score,
-        });
+// 处理 hint 命令：每次调用多显示一层提示
+fn run_hint(msg: Messages, root: &Path, exercises: &[Exercise], args: &[String]) -> io::Result<()> {
+    let name = args.first().ok_or_else(|| usage(msg, "hint <name>"))?;
+    show_next_hint(msg, root, lookup(msg, exercises, name)?)
+}
 
-        if result {
-            report.statistics.total_successes += 1;
-        } else {
-            report.statistics.total_failures += 1;
-        }
+// 多显示一层提示，并列出已显示的全部提示
+fn show_next_hint(msg: Messages, root: &Path, exercise: &Exercise) -> io::Result<()> {
+    if exercise.hints.is_empty() {
+        println!("{}", msg.format("hint.none", &[&exercise.name]));
+        return Ok(());
+    }
 
-        report.statistics.total_score += score;
+    let newly_revealed = hint::reveal_next_hint(root, exercise)?;
+    let revealed = hint::hints_used(root, &exercise.name);
+    for (level, text) in exercise.hints.iter().take(revealed).enumerate() {
+        println!("{}", msg.format("hint.line", &[&(level + 1), &exercise.hints.len(), text]));
+    }
+    if newly_revealed.is_none() {
+        println!("{}", msg.format("hint.all_revealed", &[&exercise.name]));
+    }
+    Ok(())
+}
 
-        if mode == "watch" && !ask_to_continue() {
-            break;
+// 处理 list 命令：按先修关系列出习题及上次评测的结果，未解锁的习题单独列出
+fn run_list(msg: Messages, root: &Path, config: &ExerciseConfig) -> io::Result<()> {
+    let session = SessionState::load(root, config)?;
+    let exercises = config.topological_order().unwrap_or_else(|_| config.all_exercises());
+    let passed: HashSet<String> = exercises
+        .iter()
+        .filter(|e| session.lookup(root, e).is_some_and(|r| r.result))
+        .map(|e| e.name.clone())
+        .collect();
+    let (unlocked, locked): (Vec<&Exercise>, Vec<&Exercise>) = exercises
+        .iter()
+        .partition(|e| config.locked_by(e, &passed).is_empty());
+
+    println!("{}", msg.text("list.available"));
+    for exercise in unlocked {
+        let status = match session.lookup(root, exercise) {
+            Some(result) if result.result => format!("\x1b[32m{}\x1b[0m", msg.text("status.passed")),
+            Some(_) => format!("\x1b[31m{}\x1b[0m", msg.text("status.failed")),
+            None => msg.text("status.pending").to_string(),
+        };
+        println!("  {:<16} {:<14} {}", exercise.name, exercise.exercise_type, status);
+    }
+    if !locked.is_empty() {
+        println!("\n{}", msg.text("list.locked"));
+        for exercise in locked {
+            let locked_by = config.locked_by(exercise, &passed).join(", ");
+            println!(
+                "  {:<16} {:<14} \x1b[33m{}\x1b[0m",
+                exercise.name,
+                exercise.exercise_type,
+                msg.format("list.requires", &[&locked_by])
+            );
         }
     }
+    Ok(())
 }
 
+// 处理 logs 命令：显示某道题最近一次评测各步骤的输出
+fn run_logs(msg: Messages, root: &Path, exercises: &[Exercise], args: &[String]) -> io::Result<()> {
+    let name = args.first().ok_or_else(|| usage(msg, "logs <name>"))?;
+    let exercise = lookup(msg, exercises, name)?;
 
-fn evaluate_exercise(exercise: &Exercise) -> bool {
-    let exercise_path = PathBuf::from(&format!("./exercises/{}", exercise.path));
-    match exercise.exercise_type.as_str() {
-        "single_file" => evaluate_single_file(&exercise_path),
-        "cargo_project" => evaluate_cargo_project(&exercise_path),
-        _ => {
-            eprintln!("Unknown exercise type: {}", exercise.exercise_type);
-            false
+    let (run_id, steps) = match logs::latest(root, &exercise.name)? {
+        Some(latest) => latest,
+        None => {
+            println!("{}", msg.format("logs.none", &[&exercise.name]));
+            return Ok(());
         }
+    };
+    println!("{}", msg.format("logs.run", &[&exercise.name, &run_id]));
+    for step in &steps {
+        print_step(msg, root, step);
     }
+    Ok(())
 }
 
-// 评测单文件 Rust 习题（使用 rustc --test 并执行测试）
-fn evaluate_single_file(file_path: &PathBuf) -> bool {
-    // 获取文件名（不带扩展名）
-    let test_binary = file_path.with_extension(""); 
-
-    // 编译测试文件
-    let compile_output = Command::new("rustc")
-        .arg("--test")  // 使用 rustc --test 进行编译
-        .arg(file_path)
-        .arg("-o")
-        .arg(&test_binary)  // 指定输出文件
-        .output();
-
-    if let Ok(output) = compile_output {
-        if output.status.success() {
-            // 编译成功，运行测试二进制文件
-            let test_output = Command::new(&test_binary)
-                .output();
-
-            let test_passed = match test_output {
-                Ok(test_run) => {
-                    if test_run.status.success() {
-                        println!("\x1b[32m{}: TEST PASSED\x1b[0m", file_path.display());
-                        true
-                    } else {
-                        println!("\x1b[31m{}: TEST FAILED\x1b[0m", file_path.display());
-                        false
-                    }
-                }
-                Err(_) => {
-                    eprintln!("Error running test executable for {}", file_path.display());
-                    false
-                }
-            };
-
-            // 删除测试二进制文件
-            if let Err(e) = fs::remove_file(&test_binary) {
-                eprintln!("Failed to remove test binary {}: {}", test_binary.display(), e);
-            } else {
-                println!("Successfully removed test binary: {}", test_binary.display());
+// 输出一个评测步骤的标准输出和标准错误
+fn print_step(msg: Messages, root: &Path, step: &StepLog) {
+    let exit = step.exit_code.map_or("-".to_string(), |code| code.to_string());
+    let color = if step.success { 32 } else { 31 };
+    let header = msg.format(
+        "logs.step",
+        &[&step.step, &step.command, &exit, &step.duration_ms],
+    );
+    println!("\n\x1b[{}m== {} ==\x1b[0m", color, header);
+    for (label, path) in [("stdout", &step.stdout), ("stderr", &step.stderr)] {
+        let text = fs::read_to_string(root.join(path)).unwrap_or_default();
+        if !text.trim().is_empty() {
+            println!("--- {} ({}) ---", label, path);
+            print!("{}", text);
+            if !text.ends_with('\n') {
+                println!();
             }
+        }
+    }
+}
+
+// watch 模式下显示最近一次未通过的评测中失败步骤的输出
+fn show_failure_output(msg: Messages, root: &Path, failure: Option<&ExerciseResult>) {
+    let failure = match failure {
+        Some(failure) => failure,
+        None => {
+            println!("{}", msg.text("watch.no_failure"));
+            return;
+        }
+    };
+    println!("{}", msg.format("watch.failure", &[&failure.name]));
+    // 泄漏或差分测试导致的失败没有失败步骤，此时显示全部步骤
+    let failed: Vec<&StepLog> = failure.logs.iter().filter(|step| !step.success).collect();
+    let steps = if failed.is_empty() { failure.logs.iter().collect() } else { failed };
+    for step in steps {
+        print_step(msg, root, step);
+    }
+}
 
-            return test_passed;
+// watch 模式下列出之后的习题及其解锁情况
+fn show_remaining(
+    msg: Messages,
+    config: &ExerciseConfig,
+    remaining: &[Exercise],
+    passed: &HashSet<String>,
+    current: &ExerciseResult,
+) {
+    let mut passed = passed.clone();
+    if current.result {
+        passed.insert(current.name.clone());
+    }
+    println!("{}", msg.format("watch.remaining", &[&remaining.len()]));
+    for exercise in remaining {
+        let locked_by = config.locked_by(exercise, &passed);
+        if locked_by.is_empty() {
+            println!("  {}", exercise.name);
         } else {
-            // 编译失败
-            eprintln!("\x1b[31m{}: COMPILATION FAILED\x1b[0m", file_path.display());
-            return false;
+            let note = msg.format("list.requires", &[&locked_by.join(", ")]);
+            println!("  {} \x1b[33m({})\x1b[0m", exercise.name, note);
         }
+    }
+}
+
+// 处理 verify-report 命令：校验报告签名及源码哈希
+fn run_verify_report(
+    msg: Messages,
+    root: &Path,
+    config: &ExerciseConfig,
+    args: &[String],
+) -> io::Result<()> {
+    let report_path = args.first().map(String::as_str).unwrap_or("report.json");
+    let report = Report::load(root.join(report_path))?;
+    let key = signing::load_key(root)?.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, msg.text("verify.no_key"))
+    })?;
+
+    let verification = signing::verify(&report, root, config, &key)?;
+    if !verification.key_matches {
+        println!(
+            "\x1b[31m{}\x1b[0m",
+            msg.format("verify.other_key", &[&signing_key_id(&report)])
+        );
+    }
+    if verification.signature_valid {
+        println!("\x1b[32m{}\x1b[0m", msg.text("verify.signature_valid"));
     } else {
-        eprintln!("Error executing rustc --test for {}", file_path.display());
-        return false;
+        println!("\x1b[31m{}\x1b[0m", msg.text("verify.signature_invalid"));
+    }
+    if verification.changed_sources.is_empty() {
+        println!("\x1b[32m{}\x1b[0m", msg.text("verify.sources_match"));
+    } else {
+        println!("\x1b[31m{}\x1b[0m", msg.text("verify.sources_changed"));
+        for path in &verification.changed_sources {
+            println!("  {}", path);
+        }
     }
+
+    if verification.is_valid() {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidData, report_path.to_string()))
+    }
+}
+
+// 处理 serve 命令：serve [address]，默认只监听本机
+fn run_server(msg: Messages, root: &Path, config: ExerciseConfig, args: &[String]) -> io::Result<()> {
+    let addr = args.first().map(String::as_str).unwrap_or("127.0.0.1:7878");
+    let server = Server::bind(addr, root, config)?.lang(msg.lang());
+    println!("{}", msg.format("serve.listening", &[&server.local_addr()?]));
+    server.run()
 }
 
-// 评测 Cargo 项目
-fn evaluate_cargo_project(proj_path: &PathBuf) -> bool {
-    let build_success = run_cargo_command(proj_path, "build");
-    let test_success = run_cargo_command(proj_path, "test");
-    let clippy_success = run_cargo_command(proj_path, "clippy");
+// 处理 similarity 命令：similarity <dir> [--threshold 0.8] [--format csv|html] [--output path]
+fn run_similarity(msg: Messages, root: &Path, exercises: &[Exercise], args: &[String]) -> io::Result<()> {
+    const USAGE: &str = "similarity <dir> [--threshold 0.8] [--format csv|html] [--output path]";
+    let mut dir = None;
+    let mut threshold = 0.8;
+    let mut format = "csv".to_string();
+    let mut output = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--threshold" => {
+                threshold = iter
+                    .next()
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .filter(|v| (0.0..=1.0).contains(v))
+                    .ok_or_else(|| usage(msg, USAGE))?;
+            }
+            "--format" => format = iter.next().ok_or_else(|| usage(msg, USAGE))?.clone(),
+            "--output" => output = Some(iter.next().ok_or_else(|| usage(msg, USAGE))?.clone()),
+            _ if dir.is_none() => dir = Some(arg.clone()),
+            _ => return Err(usage(msg, USAGE)),
+        }
+    }
+    let dir = dir.ok_or_else(|| usage(msg, USAGE))?;
+    if format != "csv" && format != "html" {
+        return Err(usage(msg, USAGE));
+    }
 
-    let passed = build_success && test_success && clippy_success;
+    snapshot_templates(msg, root, exercises);
+    let results = similarity::compare_submissions(root, Path::new(&dir), exercises, threshold)?;
+    for result in &results {
+        let line = msg.format(
+            "similarity.exercise",
+            &[&result.exercise, &result.pairs.len(), &result.submissions],
+        );
+        let color = if result.pairs.is_empty() { 32 } else { 33 };
+        println!("\x1b[{}m{}\x1b[0m", color, line);
+        for pair in &result.pairs {
+            println!("  {:5.1}%  {}  {}", pair.score * 100.0, pair.first, pair.second);
+        }
+    }
 
-    if passed {
-        println!("\x1b[32m{}: PASSED\x1b[0m", proj_path.display());
+    let output = output.unwrap_or_else(|| format!("similarity.{}", format));
+    let content = if format == "html" {
+        similarity::to_html(&results, threshold)
     } else {
-        println!("\x1b[31m{}: FAILED\x1b[0m", proj_path.display());
+        similarity::to_csv(&results)
+    };
+    fs::write(&output, content)?;
+    println!("{}", msg.format("similarity.written", &[&output]));
+    Ok(())
+}
+
+// 处理 grade-batch 命令：grade-batch <dir> [--jobs N] [--output gradebook] [--timeout SECS] [--sandbox]
+fn run_grade_batch(
+    msg: Messages,
+    root: &Path,
+    mut config: ExerciseConfig,
+    args: &[String],
+) -> io::Result<()> {
+    const USAGE: &str =
+        "grade-batch <dir> [--jobs N] [--output gradebook] [--timeout SECS] [--sandbox]";
+    let mut dir = None;
+    let mut jobs = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut output = "gradebook".to_string();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--jobs" => {
+                jobs = iter
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&v| v > 0)
+                    .ok_or_else(|| usage(msg, USAGE))?;
+            }
+            "--output" => output = iter.next().ok_or_else(|| usage(msg, USAGE))?.clone(),
+            "--timeout" => {
+                let secs = iter
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&v| v > 0)
+                    .ok_or_else(|| usage(msg, USAGE))?;
+                config.timeout_secs = Some(secs);
+            }
+            // 所有习题都在沙箱中评测，覆盖配置中的设置
+            "--sandbox" => {
+                config.sandbox = true;
+                for exercise in config
+                    .easy
+                    .iter_mut()
+                    .chain(&mut config.normal)
+                    .chain(&mut config.hard)
+                {
+                    exercise.sandbox = None;
+                }
+            }
+            _ if dir.is_none() => dir = Some(arg.clone()),
+            _ => return Err(usage(msg, USAGE)),
+        }
+    }
+    let dir = dir.ok_or_else(|| usage(msg, USAGE))?;
+    if batch::students(Path::new(&dir))?.is_empty() {
+        println!("{}", msg.format("batch.none", &[&dir]));
+        return Ok(());
     }
 
-    clean_target_directory(proj_path);
+    // 没有沙箱时学生代码以当前用户的权限运行，各学生的工作目录只是互相分开，并未隔离
+    let exercises = config.all_exercises();
+    if exercises.iter().any(|e| !e.sandbox.unwrap_or(config.sandbox)) {
+        eprintln!("\x1b[1;31m{}\x1b[0m", msg.text("batch.unsandboxed"));
+    }
 
-    passed
-}
+    let total = exercises.len();
+    let gradebook = batch::grade_batch(root, Path::new(&dir), &config, jobs, |grade| {
+        match &grade.error {
+            Some(error) => println!(
+                "\x1b[31m{}\x1b[0m",
+                msg.format("batch.error", &[&grade.student, error])
+            ),
+            None => println!(
+                "{}",
+                msg.format(
+                    "batch.graded",
+                    &[&grade.student, &grade.total_score, &grade.passed, &total]
+                )
+            ),
+        }
+    })?;
 
-// 运行 Cargo 命令
-fn run_cargo_command(proj_path: &PathBuf, command: &str) -> bool {
-    let output = Command::new("cargo")
-        .arg(command)
-        .current_dir(proj_path)
-        .output();
+    let csv_path = format!("{}.csv", output);
+    let json_path = format!("{}.json", output);
+    fs::write(&csv_path, gradebook.to_csv())?;
+    fs::write(&json_path, serde_json::to_string_pretty(&gradebook)?)?;
+    println!("{}", msg.format("batch.written", &[&csv_path, &json_path]));
+    Ok(())
+}
 
-    match output {
-        Ok(out) => out.status.success(),
-        Err(_) => false,
+// 处理 vendor 命令：vendor [--dir vendor] [--offline]，完成后进行离线构建检查
+fn run_vendor(msg: Messages, root: &Path, exercises: &[Exercise], args: &[String]) -> io::Result<()> {
+    const USAGE: &str = "vendor [--dir vendor] [--offline]";
+    let mut dir = vendor::DEFAULT_DIR.to_string();
+    let mut offline = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--dir" => dir = iter.next().ok_or_else(|| usage(msg, USAGE))?.clone(),
+            "--offline" => offline = true,
+            _ => return Err(usage(msg, USAGE)),
+        }
     }
+
+    let crates = vendor::vendor(root, exercises, &dir, offline)?;
+    println!("{}", msg.format("vendor.done", &[&crates, &dir, &vendor::CONFIG_FILE]));
+    run_preflight(msg, root, exercises)
 }
 
-// 清理 target 目录
-fn clean_target_directory(proj_path: &PathBuf) {
-    let target_dir = proj_path.join("target");
+// 处理 preflight 命令：逐个检查 Cargo 习题能否离线构建，存在无法构建的习题时以失败退出
+fn run_preflight(msg: Messages, root: &Path, exercises: &[Exercise]) -> io::Result<()> {
+    let cargo_exercises = vendor::cargo_exercises(root, exercises);
+    if cargo_exercises.is_empty() {
+        println!("{}", msg.text("preflight.none"));
+        return Ok(());
+    }
 
-    if target_dir.exists() {
-        if let Err(e) = fs::remove_dir_all(&target_dir) {
-            eprintln!("Failed to clean up target directory: {}", e);
-        } else {
-            println!("Successfully cleaned up target directory in: {}", proj_path.display());
+    let issues = vendor::preflight(root, exercises)?;
+    for (name, _) in &cargo_exercises {
+        match issues.iter().find(|issue| &issue.exercise == name) {
+            Some(issue) => println!(
+                "\x1b[31m{}\x1b[0m",
+                msg.format("preflight.issue", &[name, &issue.reason])
+            ),
+            None => println!("\x1b[32m{}\x1b[0m", msg.format("preflight.ok", &[name])),
         }
     }
+    if !issues.is_empty() {
+        return Err(io::Error::other(msg.format("preflight.summary", &[&issues.len()])));
+    }
+    println!("{}", msg.format("preflight.passed", &[&cargo_exercises.len()]));
+    Ok(())
 }
 
-// 用户确认是否继续
-fn ask_to_continue() -> bool {
-    let mut input = String::new();
-    println!("\nPress any key to continue, or 'q' to quit.");
-    io::stdin().read_line(&mut input).unwrap();
-    input.trim().to_lowercase() != "q"
+fn lookup<'a>(msg: Messages, exercises: &'a [Exercise], name: &str) -> io::Result<&'a Exercise> {
+    find_exercise(exercises, name).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, msg.format("exercise.unknown", &[&name]))
+    })
 }
 
-// 保存评测报告
-fn save_report_to_json(file_name: &str, report: &Report) -> io::Result<()> {
-    let file = File::create(file_name)?;
-    serde_json::to_writer_pretty(file, report)?;
-    Ok(())
+fn usage(msg: Messages, text: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.format("usage", &[&text]))
+}
+
+// 读取一个按键；终端下使用 raw 模式无需回车，输入被重定向时读取一行的首个字符
+// 输入结束或 Ctrl-C 时返回 None
+fn read_key() -> Option<char> {
+    let stdin = io::stdin();
+    if !termion::is_tty(&stdin) {
+        let mut input = String::new();
+        if stdin.read_line(&mut input).ok()? == 0 {
+            return None;
+        }
+        return Some(input.trim().chars().next().unwrap_or('\n').to_ascii_lowercase());
+    }
+
+    let stdout = io::stdout().into_raw_mode().ok()?;
+    let key = stdin.lock().keys().next();
+    drop(stdout);
+    match key?.ok()? {
+        Key::Char(c) => Some(c.to_ascii_lowercase()),
+        Key::Ctrl('c') | Key::Ctrl('d') | Key::Esc => None,
+        _ => Some('\0'),
+    }
+}
+
+fn confirm(prompt: &str) -> bool {
+    println!("{}", prompt);
+    io::stdout().flush().ok();
+    let mut input = String::new();
+    // 标准输入已关闭或读取失败时视为取消
+    if !matches!(io::stdin().read_line(&mut input), Ok(n) if n > 0) {
+        return false;
+    }
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
 }
@@ -0,0 +1,372 @@
+use crate::config::{Exercise, ExerciseConfig, ScriptSettings};
+use crate::evaluator::Log;
+use crate::i18n::Messages;
+use crate::io_program;
+use crate::logs::StepLogger;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::io;
+use std::process::{Command, Output};
+
+// 内置的习题类型
+pub const BUILTIN_TYPES: [&str; 4] = ["single_file", "cargo_project", "io_program", "doc_test"];
+
+// 某种习题类型的评测方式
+pub trait ExerciseEvaluator: Send + Sync {
+    // 评测一道习题，返回是否通过
+    fn evaluate(&self, ctx: &mut Context) -> bool;
+}
+
+// 评测一道习题时可用的信息
+pub struct Context<'a, 'b> {
+    root: &'a Path,
+    exercise: &'a Exercise,
+    msg: Messages,
+    log: &'a mut Log<'b>,
+    steps: &'a mut StepLogger,
+}
+
+impl<'a, 'b> Context<'a, 'b> {
+    pub(crate) fn new(
+        root: &'a Path,
+        exercise: &'a Exercise,
+        msg: Messages,
+        log: &'a mut Log<'b>,
+        steps: &'a mut StepLogger,
+    ) -> Self {
+        Self {
+            root,
+            exercise,
+            msg,
+            log,
+            steps,
+        }
+    }
+
+    // 包含 exercises 目录的仓库根目录
+    pub fn root(&self) -> &Path {
+        self.root
+    }
+
+    pub fn exercise(&self) -> &Exercise {
+        self.exercise
+    }
+
+    // 习题文件或目录的路径
+    pub fn path(&self) -> PathBuf {
+        self.exercise.source_path(self.root)
+    }
+
+    // 评测信息使用的语言对应的文本
+    pub fn messages(&self) -> Messages {
+        self.msg
+    }
+
+    // 输出一行评测信息，通过进度回调转发
+    pub fn log(&mut self, line: impl Into<String>) {
+        (self.log)(line.into());
+    }
+
+    // 运行一个评测步骤，输出保存到 .cargotest/logs 并记入报告
+    pub fn run(&mut self, step: &str, command: &mut Command) -> io::Result<Output> {
+        self.steps.run(step, command)
+    }
+
+    // 编译学生的代码，习题启用沙箱时在沙箱中运行
+    pub fn compile(&mut self, step: &str, command: &mut Command) -> io::Result<Output> {
+        self.steps.compile(step, command)
+    }
+
+    // 运行不可信的程序，习题启用沙箱时在沙箱中运行
+    pub fn run_untrusted(&mut self, step: &str, command: &mut Command) -> io::Result<Output> {
+        self.steps.run_untrusted(step, command)
+    }
+}
+
+// 按习题类型查找评测方式
+pub struct EvaluatorRegistry {
+    evaluators: HashMap<String, Box<dyn ExerciseEvaluator>>,
+}
+
+impl EvaluatorRegistry {
+    // 只包含内置类型
+    pub fn builtin() -> Self {
+        let mut registry = Self {
+            evaluators: HashMap::new(),
+        };
+        registry.register("single_file", SingleFile);
+        registry.register("cargo_project", CargoProject);
+        registry.register("io_program", IoProgram);
+        registry.register("doc_test", DocTest);
+        registry
+    }
+
+    // 内置类型加上配置中 custom_types 声明的脚本类型
+    pub fn for_config(config: &ExerciseConfig) -> Self {
+        let mut registry = Self::builtin();
+        for (name, settings) in &config.custom_types {
+            registry.register(name.clone(), Script::new(settings.clone()));
+        }
+        registry
+    }
+
+    pub fn register(
+        &mut self,
+        exercise_type: impl Into<String>,
+        evaluator: impl ExerciseEvaluator + 'static,
+    ) {
+        self.evaluators
+            .insert(exercise_type.into(), Box::new(evaluator));
+    }
+
+    pub fn get(&self, exercise_type: &str) -> Option<&dyn ExerciseEvaluator> {
+        self.evaluators.get(exercise_type).map(|e| e.as_ref())
+    }
+
+    pub fn contains(&self, exercise_type: &str) -> bool {
+        self.evaluators.contains_key(exercise_type)
+    }
+}
+
+struct SingleFile;
+
+impl ExerciseEvaluator for SingleFile {
+    fn evaluate(&self, ctx: &mut Context) -> bool {
+        // 在沙箱中评测时编译和运行私有副本，测试程序无法改动其他习题的源码
+        let path = ctx.path();
+        let staged = ctx.steps.sandbox().and_then(|s| s.staged_source().map(Path::to_path_buf));
+        evaluate_single_file(&path, staged.as_deref(), ctx.msg, ctx.log, ctx.steps)
+    }
+}
+
+struct CargoProject;
+
+impl ExerciseEvaluator for CargoProject {
+    fn evaluate(&self, ctx: &mut Context) -> bool {
+        evaluate_cargo_project(&ctx.path(), ctx.msg, ctx.log, ctx.steps)
+    }
+}
+
+struct IoProgram;
+
+impl ExerciseEvaluator for IoProgram {
+    fn evaluate(&self, ctx: &mut Context) -> bool {
+        let settings = &ctx.exercise.io;
+        io_program::evaluate_io_program(&ctx.path(), settings, ctx.msg, ctx.log, ctx.steps)
+    }
+}
+
+struct DocTest;
+
+impl ExerciseEvaluator for DocTest {
+    fn evaluate(&self, ctx: &mut Context) -> bool {
+        evaluate_doc_tests(&ctx.path(), ctx.msg, ctx.log, ctx.steps)
+    }
+}
+
+// 配置文件中声明的外部脚本评测
+pub struct Script {
+    settings: ScriptSettings,
+}
+
+impl Script {
+    pub fn new(settings: ScriptSettings) -> Self {
+        Self { settings }
+    }
+}
+
+impl ExerciseEvaluator for Script {
+    fn evaluate(&self, ctx: &mut Context) -> bool {
+        run_script(ctx, &self.settings)
+    }
+}
+
+// 评测单文件 Rust 习题（使用 rustc --test 并执行测试）；staged 为沙箱中使用的私有副本
+fn evaluate_single_file(
+    file_path: &Path,
+    staged: Option<&Path>,
+    msg: Messages,
+    log: &mut Log,
+    steps: &mut StepLogger,
+) -> bool {
+    let source = staged.unwrap_or(file_path);
+    // 获取文件名（不带扩展名），沙箱中的工作目录可能不同，使用绝对路径
+    let test_binary = std::path::absolute(source.with_extension(""))
+        .unwrap_or_else(|_| source.with_extension(""));
+
+    // 编译测试文件
+    let compile_output = steps.compile(
+        "compile",
+        Command::new("rustc")
+            .arg("--test") // 使用 rustc --test 进行编译
+            .arg(source)
+            .arg("-o")
+            .arg(&test_binary), // 指定输出文件
+    );
+
+    if let Ok(output) = compile_output {
+        if output.status.success() {
+            // 编译成功，运行测试二进制文件
+            let test_output = steps.run_untrusted("test", &mut Command::new(&test_binary));
+
+            let test_passed = match test_output {
+                Ok(test_run) => {
+                    if test_run.status.success() {
+                        let line = msg.format("eval.test_passed", &[&file_path.display()]);
+                        log(format!("\x1b[32m{}\x1b[0m", line));
+                        true
+                    } else {
+                        let line = msg.format("eval.test_failed", &[&file_path.display()]);
+                        log(format!("\x1b[31m{}\x1b[0m", line));
+                        false
+                    }
+                }
+                Err(e) => {
+                    log(msg.format("eval.test_error", &[&file_path.display(), &e]));
+                    false
+                }
+            };
+
+            // 删除测试二进制文件
+            if let Err(e) = fs::remove_file(&test_binary) {
+                log(msg.format("eval.binary_remove_failed", &[&test_binary.display(), &e]));
+            } else {
+                log(msg.format("eval.binary_removed", &[&test_binary.display()]));
+            }
+
+            test_passed
+        } else {
+            // 编译失败
+            let line = msg.format("eval.compile_failed", &[&file_path.display()]);
+            log(format!("\x1b[31m{}\x1b[0m", line));
+            false
+        }
+    } else {
+        log(msg.format("eval.rustc_error", &[&file_path.display()]));
+        false
+    }
+}
+
+// 评测 Cargo 项目
+fn evaluate_cargo_project(
+    proj_path: &Path,
+    msg: Messages,
+    log: &mut Log,
+    steps: &mut StepLogger,
+) -> bool {
+    let build_success = run_cargo_command(proj_path, &["build"], false, steps);
+    // 先单独编译测试，运行测试的步骤只包含测试程序本身的 CPU 时间
+    let test_success = run_cargo_command(proj_path, &["test", "--no-run"], false, steps)
+        && run_cargo_command(proj_path, &["test"], true, steps);
+    let clippy_success = run_cargo_command(proj_path, &["clippy"], false, steps);
+
+    let passed = build_success && test_success && clippy_success;
+
+    log_outcome(passed, &proj_path.display(), msg, log);
+
+    clean_target_directory(proj_path, msg, log);
+
+    passed
+}
+
+// 只运行文档测试的 Cargo 项目
+fn evaluate_doc_tests(
+    proj_path: &Path,
+    msg: Messages,
+    log: &mut Log,
+    steps: &mut StepLogger,
+) -> bool {
+    let passed = run_cargo_command(proj_path, &["test", "--doc"], true, steps);
+
+    if passed {
+        let line = msg.format("eval.doc_passed", &[&proj_path.display()]);
+        log(format!("\x1b[32m{}\x1b[0m", line));
+    } else {
+        let line = msg.format("eval.doc_failed", &[&proj_path.display()]);
+        log(format!("\x1b[31m{}\x1b[0m", line));
+    }
+
+    clean_target_directory(proj_path, msg, log);
+
+    passed
+}
+
+// 运行配置中声明的外部脚本，退出码为 0 即通过；脚本在仓库根目录下运行，{path} 替换为相对于根目录的习题路径
+fn run_script(ctx: &mut Context, settings: &ScriptSettings) -> bool {
+    let path = ctx.exercise.source_path(Path::new("."));
+    let substitute = |arg: &str| {
+        arg.replace("{path}", &path.display().to_string())
+            .replace("{name}", &ctx.exercise.name)
+    };
+    let args: Vec<String> = settings.args.iter().map(|arg| substitute(arg)).collect();
+
+    let root = ctx.root;
+    let output = match ctx.run(
+        "script",
+        Command::new(&settings.command).args(&args).current_dir(root),
+    ) {
+        Ok(output) => output,
+        Err(e) => {
+            let line = ctx.msg.format("eval.script_error", &[&settings.command, &e]);
+            ctx.log(line);
+            return false;
+        }
+    };
+
+    for line in String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+    {
+        ctx.log(line);
+    }
+
+    let passed = output.status.success();
+    log_outcome(passed, &path.display(), ctx.msg, ctx.log);
+    passed
+}
+
+// 运行 Cargo 命令，以参数作为步骤名称记录输出；所有命令都会编译并执行学生的代码（构建脚本、过程宏），
+// 启用沙箱时都在沙箱中运行，measure 的命令（运行测试）计入 CPU 时间
+fn run_cargo_command(
+    proj_path: &Path,
+    args: &[&str],
+    measure: bool,
+    steps: &mut StepLogger,
+) -> bool {
+    let mut command = Command::new("cargo");
+    command.args(args).current_dir(proj_path);
+    let output = if measure {
+        steps.run_untrusted(&args.join(" "), &mut command)
+    } else {
+        steps.compile(&args.join(" "), &mut command)
+    };
+
+    match output {
+        Ok(out) => out.status.success(),
+        Err(_) => false,
+    }
+}
+
+// 输出习题通过或未通过
+pub(crate) fn log_outcome(passed: bool, name: &dyn fmt::Display, msg: Messages, log: &mut Log) {
+    if passed {
+        log(format!("\x1b[32m{}\x1b[0m", msg.format("eval.passed", &[name])));
+    } else {
+        log(format!("\x1b[31m{}\x1b[0m", msg.format("eval.failed", &[name])));
+    }
+}
+
+// 清理 target 目录
+pub(crate) fn clean_target_directory(proj_path: &Path, msg: Messages, log: &mut Log) {
+    let target_dir = proj_path.join("target");
+
+    if target_dir.exists() {
+        if let Err(e) = fs::remove_dir_all(&target_dir) {
+            log(msg.format("eval.target_clean_failed", &[&e]));
+        } else {
+            log(msg.format("eval.target_cleaned", &[&proj_path.display()]));
+        }
+    }
+}
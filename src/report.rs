@@ -0,0 +1,211 @@
+use crate::complexity::ComplexityEstimate;
+use crate::config::{CategoryPolicy, Exercise, ExerciseConfig};
+use crate::constraints::Violation;
+use crate::differential::Counterexample;
+use crate::environment::{unix_time, Environment};
+use crate::leak::TestLeak;
+use crate::logs::StepLog;
+use crate::signing::ReportSignature;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExerciseResult {
+    pub name: String,
+    pub result: bool,
+    pub score: i32,
+    #[serde(default)]
+    pub hints_used: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leaks: Vec<TestLeak>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterexample: Option<Counterexample>,
+    // 违反静态约束的代码位置
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<Violation>,
+    // 估计的时间复杂度与各规模的耗时
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complexity: Option<ComplexityEstimate>,
+    // 被沙箱终止的步骤及原因
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sandbox_violations: Vec<String>,
+    // 最后一次评测中测试程序（或学生程序）所用的 CPU 时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_ms: Option<u64>,
+    // CPU 时间超过了题目配置的上限
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cpu_budget_exceeded: bool,
+    // 评测超过了时间上限，正在运行的命令被终止
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    // 每次评测的结果与耗时
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<Attempt>,
+    // 重试后才通过
+    #[serde(default)]
+    pub flaky: bool,
+    // 各评测步骤的输出文件
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<StepLog>,
+    // 沿用之前运行的结果，本次未重新评测
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub resumed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Attempt {
+    pub passed: bool,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_ms: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Statistics {
+    pub total_exercises: usize,
+    pub total_successes: usize,
+    pub total_failures: usize,
+    pub total_score: i32,
+    pub total_time: u64,
+    #[serde(default)]
+    pub total_flaky: usize,
+}
+
+// 某一难度的得分统计
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CategoryStatistics {
+    pub name: String,
+    pub total_exercises: usize,
+    pub total_successes: usize,
+    // 应用评分规则前的得分
+    pub raw_score: i32,
+    pub score: i32,
+    pub max_score: i32,
+    pub percentage: f64,
+    pub requirement_met: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Report {
+    pub exercises: Vec<ExerciseResult>,
+    pub statistics: Statistics,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<CategoryStatistics>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Environment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ReportSignature>,
+}
+
+impl Report {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 记录一道题的评测结果并更新统计
+    pub fn record(&mut self, result: ExerciseResult) {
+        if result.result {
+            self.statistics.total_successes += 1;
+        } else {
+            self.statistics.total_failures += 1;
+        }
+        self.statistics.total_score += result.score;
+        if result.flaky {
+            self.statistics.total_flaky += 1;
+        }
+        self.statistics.total_exercises =
+            self.statistics.total_successes + self.statistics.total_failures;
+        self.exercises.push(result);
+    }
+
+    // 用新的评测结果替换同名习题的记录并重新统计
+    pub fn replace(&mut self, result: ExerciseResult) {
+        match self.exercises.iter_mut().find(|r| r.name == result.name) {
+            Some(existing) => *existing = result,
+            None => self.exercises.push(result),
+        }
+        let successes = self.exercises.iter().filter(|r| r.result).count();
+        self.statistics.total_exercises = self.exercises.len();
+        self.statistics.total_successes = successes;
+        self.statistics.total_failures = self.exercises.len() - successes;
+        self.statistics.total_score = self.exercises.iter().map(|r| r.score).sum();
+        self.statistics.total_flaky = self.exercises.iter().filter(|r| r.flaky).count();
+    }
+
+    // 结束评测：按难度汇总得分，应用上限和最低通过数规则后重新计算总分
+    pub fn finish(&mut self, config: &ExerciseConfig, elapsed: Duration) {
+        self.statistics.total_time = elapsed.as_secs();
+        if let Some(environment) = self.environment.as_mut() {
+            environment.finished_at = unix_time();
+        }
+
+        let default_policy = CategoryPolicy::default();
+        self.categories = config
+            .category_exercises()
+            .into_iter()
+            .map(|(name, exercises)| {
+                let policy = config.categories.get(name).unwrap_or(&default_policy);
+                self.category_statistics(name, exercises, policy)
+            })
+            .collect();
+        self.statistics.total_score = self.categories.iter().map(|c| c.score).sum();
+    }
+
+    fn category_statistics(
+        &self,
+        name: &str,
+        exercises: &[Exercise],
+        policy: &CategoryPolicy,
+    ) -> CategoryStatistics {
+        let results: Vec<&ExerciseResult> = self
+            .exercises
+            .iter()
+            .filter(|r| exercises.iter().any(|e| e.name == r.name))
+            .collect();
+        let total_successes = results.iter().filter(|r| r.result).count();
+        let raw_score: i32 = results.iter().map(|r| r.score).sum();
+
+        let mut max_score = policy
+            .max_score
+            .unwrap_or_else(|| exercises.iter().map(|e| e.score).sum());
+        let mut score = raw_score;
+        if let Some(cap) = policy.cap {
+            max_score = max_score.min(cap);
+            score = score.min(cap);
+        }
+        let requirement_met = total_successes >= policy.min_passed;
+        if !requirement_met {
+            score = 0;
+        }
+
+        CategoryStatistics {
+            name: name.to_string(),
+            total_exercises: exercises.len(),
+            total_successes,
+            raw_score,
+            score,
+            max_score,
+            percentage: if max_score > 0 {
+                score as f64 * 100.0 / max_score as f64
+            } else {
+                0.0
+            },
+            requirement_met,
+        }
+    }
+
+    pub fn load(file_path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(file_path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    // 保存评测报告
+    pub fn save(&self, file_path: impl AsRef<Path>) -> io::Result<()> {
+        let file = File::create(file_path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
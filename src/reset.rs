@@ -0,0 +1,201 @@
+use crate::config::Exercise;
+use crate::files::{copy_file, relative_files, resolve};
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// 习题原始模板的保存目录，相对于仓库根目录
+const TEMPLATE_DIR: &str = ".cargotest/templates";
+// 记录每道题的模板取自哪些 git 对象，上游版本更新后据此刷新模板
+const TEMPLATE_INDEX: &str = ".cargotest/templates/index.json";
+
+// 上游版本所在的引用，按顺序取第一个存在的：维护者打的标签、upstream 远程、直接克隆时的 origin
+const UPSTREAM_REFS: [&str; 5] = [
+    "cargotest-template",
+    "upstream/HEAD",
+    "upstream/main",
+    "upstream/master",
+    "origin/HEAD",
+];
+
+// 从 git 历史中取出习题的原始模板：上游版本为当前提交与上游引用的共同祖先，即学生拿到的最新上游提交，
+// 不使用磁盘上的当前文件或学生的提交，以免把学生的修改当作模板。上游版本中的习题有变化时刷新模板，
+// 没有 git 历史的习题不保存模板
+pub fn snapshot_templates(root: &Path, exercises: &[Exercise]) -> io::Result<()> {
+    let Some(commit) = upstream_commit(root) else {
+        return Ok(());
+    };
+    let Some(listing) = git(root, &["ls-tree", "-r", &commit, "--", "exercises"]) else {
+        return Ok(());
+    };
+    let listing = String::from_utf8_lossy(&listing).into_owned();
+    let index_path = root.join(TEMPLATE_INDEX);
+    let mut index: BTreeMap<String, String> = fs::read_to_string(&index_path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+
+    let mut changed = false;
+    for exercise in exercises {
+        let pathspec = format!("exercises/{}", exercise.path);
+        // ls-tree 每行为 "<模式> blob <对象>\t<路径>"，对象和路径一起标识该版本的模板
+        let blobs: Vec<&str> = listing
+            .lines()
+            .filter(|line| {
+                line.split_once('\t')
+                    .is_some_and(|(_, name)| Path::new(name).strip_prefix(&pathspec).is_ok())
+            })
+            .collect();
+        if blobs.is_empty() {
+            continue;
+        }
+        let version = blobs.join("\n");
+        let template = template_path(root, exercise);
+        if template.exists() && index.get(&exercise.path) == Some(&version) {
+            continue;
+        }
+        let Some(files) = template_files(root, &commit, &pathspec) else {
+            continue;
+        };
+        // 全部读出后再写入，避免留下不完整的模板
+        if template.is_dir() {
+            fs::remove_dir_all(&template)?;
+        } else if template.exists() {
+            fs::remove_file(&template)?;
+        }
+        for (rel, contents) in files {
+            let target = resolve(&template, &rel);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(target, contents)?;
+        }
+        index.insert(exercise.path.clone(), version);
+        changed = true;
+    }
+    if changed {
+        let text = serde_json::to_string_pretty(&index).map_err(io::Error::other)?;
+        fs::write(index_path, text)?;
+    }
+    Ok(())
+}
+
+// 上游版本所在的提交；仓库没有配置远程时（维护者自己的仓库）全部提交都来自上游，取当前提交
+fn upstream_commit(root: &Path) -> Option<String> {
+    let commit = |output: Vec<u8>| {
+        let text = String::from_utf8_lossy(&output).trim().to_string();
+        (!text.is_empty()).then_some(text)
+    };
+    for reference in UPSTREAM_REFS {
+        let spec = format!("{}^{{commit}}", reference);
+        if git(root, &["rev-parse", "--verify", "--quiet", &spec]).is_some() {
+            return git(root, &["merge-base", "HEAD", reference]).and_then(commit);
+        }
+    }
+    let remotes = git(root, &["remote"])?;
+    if !remotes.is_empty() {
+        return None;
+    }
+    git(root, &["rev-parse", "--verify", "--quiet", "HEAD"]).and_then(commit)
+}
+
+// 上游提交中该路径下的全部文件（相对于习题路径）及其内容
+fn template_files(root: &Path, commit: &str, pathspec: &str) -> Option<Vec<(PathBuf, Vec<u8>)>> {
+    let tree = git(
+        root,
+        &["ls-tree", "-r", "--name-only", commit, "--", pathspec],
+    )?;
+
+    let mut files = Vec::new();
+    for name in String::from_utf8_lossy(&tree).lines() {
+        let rel = Path::new(name).strip_prefix(pathspec).ok()?.to_path_buf();
+        let contents = git(
+            root,
+            &["cat-file", "blob", &format!("{}:./{}", commit, name)],
+        )?;
+        files.push((rel, contents));
+    }
+    if files.is_empty() {
+        None
+    } else {
+        Some(files)
+    }
+}
+
+// 在 root 下运行 git 命令，失败时返回 None
+fn git(root: &Path, args: &[&str]) -> Option<Vec<u8>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .ok()?;
+    output.status.success().then_some(output.stdout)
+}
+
+pub fn has_template(root: &Path, exercise: &Exercise) -> bool {
+    template_path(root, exercise).exists()
+}
+
+pub(crate) fn template_path(root: &Path, exercise: &Exercise) -> PathBuf {
+    root.join(TEMPLATE_DIR).join(&exercise.path)
+}
+
+// 生成当前文件相对于模板的 unified diff，每个有差异的文件一段
+pub fn pending_changes(root: &Path, exercise: &Exercise) -> io::Result<Vec<String>> {
+    let source = exercise.source_path(root);
+    let template = template_path(root, exercise);
+
+    let mut files = relative_files(&template)?;
+    for rel in relative_files(&source)? {
+        if !files.contains(&rel) {
+            files.push(rel);
+        }
+    }
+    files.sort();
+
+    let mut diffs = Vec::new();
+    for rel in files {
+        let current_file = resolve(&source, &rel);
+        let template_file = resolve(&template, &rel);
+        let current = read_lossy(&current_file);
+        let pristine = read_lossy(&template_file);
+        if current == pristine {
+            continue;
+        }
+        let diff = TextDiff::from_lines(&current, &pristine)
+            .unified_diff()
+            .header(
+                &current_file.display().to_string(),
+                &template_file.display().to_string(),
+            )
+            .to_string();
+        diffs.push(diff);
+    }
+    Ok(diffs)
+}
+
+// 用模板覆盖习题，删除模板中不存在的文件
+pub fn restore(root: &Path, exercise: &Exercise) -> io::Result<()> {
+    let source = exercise.source_path(root);
+    let template = template_path(root, exercise);
+    let pristine = relative_files(&template)?;
+
+    for rel in relative_files(&source)? {
+        if !pristine.contains(&rel) {
+            fs::remove_file(resolve(&source, &rel))?;
+        }
+    }
+    for rel in pristine {
+        copy_file(&resolve(&template, &rel), &resolve(&source, &rel))?;
+    }
+    Ok(())
+}
+
+fn read_lossy(path: &Path) -> String {
+    fs::read(path)
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,757 @@
+use crate::config::Exercise;
+use crate::cpu::{self, Finished};
+use crate::vendor;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::time::Duration;
+
+// 挂载沙箱根目录所用的空目录
+const STAGING_DIR: &str = ".cargotest/sandbox";
+// 插桩后的测试程序所在目录，沙箱内只读
+const INSTRUMENTED_DIR: &str = ".cargotest/instrumented";
+// 单文件习题在沙箱中编译和运行时使用的私有副本所在目录，每道题一个子目录
+const WORK_DIR: &str = ".cargotest/work";
+
+// 在 Linux 命名空间中运行不可信的测试程序：
+// 只能读取系统目录和 Rust 工具链，只能写入习题所在目录（单文件习题为其私有副本所在目录）和私有的 /tmp，
+// 没有网络，看不到其他进程，危险的系统调用由 seccomp 过滤器终止
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    staging: PathBuf,
+    writable: Vec<PathBuf>,
+    read_only: Vec<PathBuf>,
+    // 单文件习题的私有副本，在沙箱中编译和运行的是这份副本
+    staged: Option<PathBuf>,
+}
+
+impl Sandbox {
+    // Cargo 习题的目录可写；单文件习题复制到 .cargotest/work 下的私有目录，只有该目录可写，
+    // 测试程序无法改动同一目录下其他习题的源码。插桩目录、仓库的 Cargo 配置和依赖源码只读
+    pub fn for_exercise(root: &Path, exercise: &Exercise) -> io::Result<Self> {
+        if !imp::SUPPORTED {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the sandbox requires Linux on x86_64 or aarch64",
+            ));
+        }
+        let staging = std::path::absolute(root.join(STAGING_DIR))?;
+        let instrumented = std::path::absolute(root.join(INSTRUMENTED_DIR))?;
+        fs::create_dir_all(&staging)?;
+        fs::create_dir_all(&instrumented)?;
+
+        // 仓库的 Cargo 配置和下载的依赖源码只读，离线构建配置在沙箱中同样生效
+        let mut read_only = vec![instrumented];
+        for dir in [".cargo", vendor::DEFAULT_DIR] {
+            if root.join(dir).is_dir() {
+                read_only.push(std::path::absolute(root.join(dir))?);
+            }
+        }
+
+        let source = std::path::absolute(exercise.source_path(root))?;
+        if source.is_dir() {
+            return Ok(Self {
+                staging,
+                writable: vec![source],
+                read_only,
+                staged: None,
+            });
+        }
+        let file_name = source
+            .file_name()
+            .ok_or_else(|| io::Error::other(format!("{} is not a file", source.display())))?;
+        let workdir = std::path::absolute(root.join(WORK_DIR).join(file_name))?;
+        if workdir.exists() {
+            fs::remove_dir_all(&workdir)?;
+        }
+        fs::create_dir_all(&workdir)?;
+        let staged = workdir.join(file_name);
+        if source.is_file() {
+            fs::copy(&source, &staged)?;
+        }
+        Ok(Self {
+            staging,
+            writable: vec![workdir],
+            read_only,
+            staged: Some(staged),
+        })
+    }
+
+    // 单文件习题的私有副本
+    pub fn staged_source(&self) -> Option<&Path> {
+        self.staged.as_deref()
+    }
+
+    // 额外允许读取 read_only、写入 writable 的沙箱，用于在沙箱中编译插桩后的源码
+    pub(crate) fn allowing(&self, read_only: &[PathBuf], writable: &[PathBuf]) -> io::Result<Self> {
+        let mut sandbox = self.clone();
+        for path in read_only {
+            sandbox.read_only.push(std::path::absolute(path)?);
+        }
+        for path in writable {
+            sandbox.writable.push(std::path::absolute(path)?);
+        }
+        Ok(sandbox)
+    }
+
+    // 让命令在沙箱中运行；沙箱在子进程中建立，失败时 spawn 返回错误
+    pub fn apply(&self, command: &mut Command) -> io::Result<()> {
+        // 沙箱内没有网络，cargo 不应尝试联网
+        command
+            .env("CARGO_NET_OFFLINE", "true")
+            .env("TMPDIR", "/tmp");
+        imp::apply(self, command)
+    }
+}
+
+// 测试程序因违反沙箱限制被终止时返回原因
+pub fn violation(status: &ExitStatus) -> Option<String> {
+    imp::violation(status)
+}
+
+// 运行命令并等待结束，指定沙箱时在沙箱中运行，被沙箱终止视为错误；超过 timeout 时终止命令
+pub(crate) fn output(
+    command: &mut Command,
+    sandbox: Option<&Sandbox>,
+    timeout: Option<Duration>,
+) -> io::Result<Finished> {
+    if let Some(sandbox) = sandbox {
+        sandbox.apply(command)?;
+    }
+    let finished = cpu::output(command, timeout)?;
+    match violation(&finished.output.status) {
+        Some(violation) => Err(io::Error::other(format!(
+            "sandbox violation: {}",
+            violation
+        ))),
+        None => Ok(finished),
+    }
+}
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod imp {
+    use super::Sandbox;
+    use libc::{c_int, c_ulong, sock_filter, sock_fprog};
+    use std::collections::HashSet;
+    use std::ffi::CString;
+    use std::fs;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::process::{CommandExt, ExitStatusExt};
+    use std::path::{Path, PathBuf};
+    use std::process::{Command, ExitStatus};
+
+    pub const SUPPORTED: bool = true;
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xC000_003E;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xC000_00B7;
+
+    // seccomp_data 中系统调用号、架构和第一个参数（低 32 位）的偏移
+    const DATA_NR: u32 = 0;
+    const DATA_ARCH: u32 = 4;
+    const DATA_ARG0: u32 = 16;
+
+    // 只读挂载的系统目录，是符号链接时在沙箱中创建同样的链接
+    const SYSTEM_DIRS: [&str; 6] = ["/usr", "/bin", "/sbin", "/lib", "/lib64", "/etc"];
+    const DEVICES: [&str; 5] = [
+        "/dev/null",
+        "/dev/zero",
+        "/dev/full",
+        "/dev/random",
+        "/dev/urandom",
+    ];
+    const DEVICE_LINKS: [(&str, &str); 4] = [
+        ("/proc/self/fd", "/dev/fd"),
+        ("/proc/self/fd/0", "/dev/stdin"),
+        ("/proc/self/fd/1", "/dev/stdout"),
+        ("/proc/self/fd/2", "/dev/stderr"),
+    ];
+    // 工具链目录中沙箱内可见的部分，其余（如凭据）被空的 tmpfs 遮住
+    const CARGO_HOME_ENTRIES: [&str; 5] = ["bin", "registry", "git", "config.toml", "config"];
+
+    // 不可信代码不需要的系统调用，调用即终止进程
+    const BLOCKED: &[libc::c_long] = &[
+        libc::SYS_ptrace,
+        libc::SYS_process_vm_readv,
+        libc::SYS_process_vm_writev,
+        libc::SYS_mount,
+        libc::SYS_umount2,
+        libc::SYS_pivot_root,
+        libc::SYS_chroot,
+        libc::SYS_unshare,
+        libc::SYS_setns,
+        libc::SYS_open_tree,
+        libc::SYS_move_mount,
+        libc::SYS_fsopen,
+        libc::SYS_fsconfig,
+        libc::SYS_fsmount,
+        libc::SYS_fspick,
+        libc::SYS_mount_setattr,
+        libc::SYS_kexec_load,
+        libc::SYS_kexec_file_load,
+        libc::SYS_init_module,
+        libc::SYS_finit_module,
+        libc::SYS_delete_module,
+        libc::SYS_reboot,
+        libc::SYS_swapon,
+        libc::SYS_swapoff,
+        libc::SYS_bpf,
+        libc::SYS_perf_event_open,
+        libc::SYS_keyctl,
+        libc::SYS_add_key,
+        libc::SYS_request_key,
+        libc::SYS_userfaultfd,
+        libc::SYS_open_by_handle_at,
+        libc::SYS_acct,
+        libc::SYS_settimeofday,
+        libc::SYS_clock_settime,
+    ];
+
+    const NAMESPACE_FLAGS: c_int = libc::CLONE_NEWUSER
+        | libc::CLONE_NEWNS
+        | libc::CLONE_NEWNET
+        | libc::CLONE_NEWPID
+        | libc::CLONE_NEWIPC
+        | libc::CLONE_NEWUTS
+        | libc::CLONE_NEWCGROUP;
+
+    // 在子进程中依次执行的挂载操作，路径都位于新根目录下
+    enum Op {
+        Dir(CString),
+        File(CString),
+        Tmpfs(CString),
+        Proc(CString),
+        Symlink {
+            target: CString,
+            link: CString,
+        },
+        Bind {
+            source: CString,
+            target: CString,
+            remount: Option<c_ulong>,
+        },
+    }
+
+    // 子进程中不能安全地分配内存，所需的全部数据在 fork 之前准备好
+    struct Plan {
+        new_root: PathBuf,
+        ops: Vec<Op>,
+        created: HashSet<PathBuf>,
+    }
+
+    impl Plan {
+        fn new(new_root: &Path) -> io::Result<Self> {
+            let mut plan = Plan {
+                new_root: new_root.to_path_buf(),
+                ops: Vec::new(),
+                created: HashSet::new(),
+            };
+            plan.ops.push(Op::Tmpfs(cstring(new_root)?));
+            plan.created.insert(PathBuf::from("/"));
+            Ok(plan)
+        }
+
+        fn inside(&self, path: &Path) -> PathBuf {
+            self.new_root.join(path.strip_prefix("/").unwrap_or(path))
+        }
+
+        // 创建 path 及其尚未创建的上级目录
+        fn dir(&mut self, path: &Path) -> io::Result<()> {
+            let mut missing = Vec::new();
+            for ancestor in path.ancestors() {
+                if self.created.contains(ancestor) {
+                    break;
+                }
+                missing.push(ancestor.to_path_buf());
+            }
+            for dir in missing.into_iter().rev() {
+                self.ops.push(Op::Dir(cstring(&self.inside(&dir))?));
+                self.created.insert(dir);
+            }
+            Ok(())
+        }
+
+        fn tmpfs(&mut self, path: &Path) -> io::Result<()> {
+            self.dir(path)?;
+            self.ops.push(Op::Tmpfs(cstring(&self.inside(path))?));
+            Ok(())
+        }
+
+        fn symlink(&mut self, target: &Path, link: &Path) -> io::Result<()> {
+            if let Some(parent) = link.parent() {
+                self.dir(parent)?;
+            }
+            self.ops.push(Op::Symlink {
+                target: cstring(target)?,
+                link: cstring(&self.inside(link))?,
+            });
+            self.created.insert(link.to_path_buf());
+            Ok(())
+        }
+
+        // 绑定挂载已存在的文件或目录，不存在时忽略
+        fn bind(&mut self, path: &Path, writable: bool) -> io::Result<()> {
+            let metadata = match fs::metadata(path) {
+                Ok(metadata) => metadata,
+                Err(_) => return Ok(()),
+            };
+            if metadata.is_dir() {
+                self.dir(path)?;
+            } else {
+                if let Some(parent) = path.parent() {
+                    self.dir(parent)?;
+                }
+                self.ops.push(Op::File(cstring(&self.inside(path))?));
+                self.created.insert(path.to_path_buf());
+            }
+            let remount = if writable {
+                None
+            } else {
+                Some(read_only_flags(path)?)
+            };
+            self.ops.push(Op::Bind {
+                source: cstring(path)?,
+                target: cstring(&self.inside(path))?,
+                remount,
+            });
+            Ok(())
+        }
+    }
+
+    // 在子进程中使用的全部数据
+    struct Prepared {
+        uid_map: Vec<u8>,
+        gid_map: Vec<u8>,
+        ops: Vec<Op>,
+        new_root: CString,
+        workdir: CString,
+        cwd: Vec<u8>,
+        filter: Vec<sock_filter>,
+    }
+
+    pub fn apply(sandbox: &Sandbox, command: &mut Command) -> io::Result<()> {
+        let mut plan = Plan::new(&sandbox.staging)?;
+        // 可写目录可能位于 /tmp 下，先挂载 /tmp 以免遮住它们
+        plan.tmpfs(Path::new("/tmp"))?;
+        plan.dir(Path::new("/proc"))?;
+        let proc_dir = cstring(&plan.inside(Path::new("/proc")))?;
+        plan.ops.push(Op::Proc(proc_dir));
+
+        for dir in SYSTEM_DIRS.iter().map(Path::new) {
+            match fs::symlink_metadata(dir) {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    plan.symlink(&fs::read_link(dir)?, dir)?
+                }
+                Ok(_) => plan.bind(dir, false)?,
+                Err(_) => {}
+            }
+        }
+
+        plan.dir(Path::new("/dev"))?;
+        for device in DEVICES.iter().map(Path::new) {
+            plan.bind(device, true)?;
+        }
+        for (target, link) in DEVICE_LINKS {
+            plan.symlink(Path::new(target), Path::new(link))?;
+        }
+
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let tool_dir = |var: &str, default: &str| {
+            std::env::var_os(var)
+                .map(PathBuf::from)
+                .or_else(|| home.as_ref().map(|home| home.join(default)))
+                .and_then(|dir| fs::canonicalize(dir).ok())
+        };
+        if let Some(cargo_home) = tool_dir("CARGO_HOME", ".cargo") {
+            plan.tmpfs(&cargo_home)?;
+            for entry in CARGO_HOME_ENTRIES {
+                plan.bind(&cargo_home.join(entry), false)?;
+            }
+        }
+        if let Some(rustup_home) = tool_dir("RUSTUP_HOME", ".rustup") {
+            plan.bind(&rustup_home, false)?;
+        }
+
+        for dir in &sandbox.read_only {
+            plan.bind(&fs::canonicalize(dir)?, false)?;
+        }
+        for dir in &sandbox.writable {
+            plan.bind(&fs::canonicalize(dir)?, true)?;
+        }
+
+        let workdir = sandbox
+            .writable
+            .first()
+            .map(|dir| cstring(dir))
+            .unwrap_or_else(|| cstring(Path::new("/")))?;
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        let mut prepared = Prepared {
+            uid_map: format!("{0} {0} 1\n", uid).into_bytes(),
+            gid_map: format!("{0} {0} 1\n", gid).into_bytes(),
+            ops: plan.ops,
+            new_root: cstring(&sandbox.staging)?,
+            workdir,
+            cwd: vec![0; libc::PATH_MAX as usize],
+            filter: seccomp_filter(),
+        };
+        unsafe {
+            command.pre_exec(move || enter(&mut prepared));
+        }
+        Ok(())
+    }
+
+    pub fn violation(status: &ExitStatus) -> Option<String> {
+        (status.signal() == Some(libc::SIGSYS)).then(|| {
+            "killed by SIGSYS: blocked system call (network access, ptrace, mounting \
+             or namespace changes are not allowed in the sandbox)"
+                .to_string()
+        })
+    }
+
+    // 在 fork 出的子进程中运行：
+    // 当前进程创建命名空间后作为监视进程等待；
+    // 新 PID 命名空间的 1 号进程建立文件系统后再 fork 出实际运行程序的进程，
+    // 使程序不具有 1 号进程忽略信号的特殊语义，其退出状态经管道逐级传回
+    fn enter(p: &mut Prepared) -> io::Result<()> {
+        unsafe {
+            check(libc::unshare(
+                libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWNET | libc::CLONE_NEWPID,
+            ))?;
+            write_file(c"/proc/self/setgroups", b"deny")?;
+            write_file(c"/proc/self/uid_map", &p.uid_map)?;
+            write_file(c"/proc/self/gid_map", &p.gid_map)?;
+
+            let mut fds = [0; 2];
+            check(libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC))?;
+            let init = check(libc::fork())?;
+            if init > 0 {
+                libc::close(fds[1]);
+                relay(init, fds[0]);
+            }
+            libc::close(fds[0]);
+            check(libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL))?;
+
+            if libc::getcwd(p.cwd.as_mut_ptr().cast(), p.cwd.len()).is_null() {
+                p.cwd[0] = 0;
+            }
+            check(libc::mount(
+                std::ptr::null(),
+                c"/".as_ptr(),
+                std::ptr::null(),
+                libc::MS_REC | libc::MS_PRIVATE,
+                std::ptr::null(),
+            ))?;
+            for op in &p.ops {
+                mount_op(op)?;
+            }
+            check(libc::chdir(p.new_root.as_ptr()))?;
+            check(libc::syscall(libc::SYS_pivot_root, c".".as_ptr(), c".".as_ptr()) as c_int)?;
+            check(libc::umount2(c".".as_ptr(), libc::MNT_DETACH))?;
+            if p.cwd[0] == 0 || libc::chdir(p.cwd.as_ptr().cast()) != 0 {
+                check(libc::chdir(p.workdir.as_ptr()))?;
+            }
+
+            let child = check(libc::fork())?;
+            if child > 0 {
+                reap(child, fds[1]);
+            }
+            libc::close(fds[1]);
+            check(libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL))?;
+
+            let no_core = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            check(libc::setrlimit(libc::RLIMIT_CORE, &no_core))?;
+            check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0))?;
+            let program = sock_fprog {
+                len: p.filter.len() as u16,
+                filter: p.filter.as_mut_ptr(),
+            };
+            check(libc::prctl(
+                libc::PR_SET_SECCOMP,
+                libc::SECCOMP_MODE_FILTER,
+                &program as *const sock_fprog,
+            ))?;
+        }
+        Ok(())
+    }
+
+    // 命名空间外的监视进程：读取 1 号进程传回的退出状态，以同样的方式退出
+    unsafe fn relay(init: libc::pid_t, status_pipe: c_int) -> ! {
+        close_except(status_pipe);
+        let mut buffer = [0u8; 4];
+        let mut read = 0;
+        while read < buffer.len() {
+            let n = libc::read(
+                status_pipe,
+                buffer[read..].as_mut_ptr().cast(),
+                buffer.len() - read,
+            );
+            if n > 0 {
+                read += n as usize;
+            } else if n == 0 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+                break;
+            }
+        }
+        let init_status = wait_for(init);
+        // 1 号进程在启动程序之前失败时没有传回状态
+        exit_like(if read == buffer.len() {
+            c_int::from_ne_bytes(buffer)
+        } else {
+            init_status
+        })
+    }
+
+    // 命名空间内的 1 号进程：等待程序结束并传回其退出状态
+    unsafe fn reap(child: libc::pid_t, status_pipe: c_int) -> ! {
+        close_except(status_pipe);
+        let status = wait_for(child).to_ne_bytes();
+        libc::write(status_pipe, status.as_ptr().cast(), status.len());
+        libc::_exit(0)
+    }
+
+    unsafe fn wait_for(pid: libc::pid_t) -> c_int {
+        let mut status = 0;
+        while libc::waitpid(pid, &mut status, 0) < 0 {
+            if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+                return 127 << 8;
+            }
+        }
+        status
+    }
+
+    unsafe fn exit_like(status: c_int) -> ! {
+        if libc::WIFSIGNALED(status) {
+            let signal = libc::WTERMSIG(status);
+            let no_core = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            libc::setrlimit(libc::RLIMIT_CORE, &no_core);
+            libc::signal(signal, libc::SIG_DFL);
+            libc::kill(libc::getpid(), signal);
+            libc::_exit(128 + signal)
+        }
+        libc::_exit(if libc::WIFEXITED(status) {
+            libc::WEXITSTATUS(status)
+        } else {
+            1
+        })
+    }
+
+    // 关闭标准输入输出之外的文件描述符，否则父进程等待的 exec 错误管道不会关闭
+    unsafe fn close_except(keep: c_int) {
+        for (first, last) in [(3, keep - 1), (keep + 1, c_int::MAX)] {
+            if first > last {
+                continue;
+            }
+            if libc::syscall(libc::SYS_close_range, first as u32, last as u32, 0) != 0 {
+                for fd in first..last.min(1024) {
+                    libc::close(fd);
+                }
+            }
+        }
+    }
+
+    unsafe fn mount_op(op: &Op) -> io::Result<()> {
+        let null = std::ptr::null();
+        match op {
+            Op::Dir(path) => {
+                if libc::mkdir(path.as_ptr(), 0o755) != 0
+                    && io::Error::last_os_error().raw_os_error() != Some(libc::EEXIST)
+                {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Op::File(path) => {
+                let fd = check(libc::open(
+                    path.as_ptr(),
+                    libc::O_CREAT | libc::O_WRONLY | libc::O_CLOEXEC,
+                    0o644,
+                ))?;
+                libc::close(fd);
+            }
+            Op::Tmpfs(path) => check(libc::mount(
+                c"tmpfs".as_ptr(),
+                path.as_ptr(),
+                c"tmpfs".as_ptr(),
+                libc::MS_NOSUID | libc::MS_NODEV,
+                c"mode=0755".as_ptr().cast(),
+            ))
+            .map(drop)?,
+            Op::Proc(path) => check(libc::mount(
+                c"proc".as_ptr(),
+                path.as_ptr(),
+                c"proc".as_ptr(),
+                libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+                null,
+            ))
+            .map(drop)?,
+            Op::Symlink { target, link } => {
+                check(libc::symlink(target.as_ptr(), link.as_ptr()))?;
+            }
+            Op::Bind {
+                source,
+                target,
+                remount,
+            } => {
+                check(libc::mount(
+                    source.as_ptr(),
+                    target.as_ptr(),
+                    std::ptr::null(),
+                    libc::MS_BIND | libc::MS_REC,
+                    null,
+                ))?;
+                if let Some(flags) = remount {
+                    check(libc::mount(
+                        std::ptr::null(),
+                        target.as_ptr(),
+                        std::ptr::null(),
+                        *flags,
+                        null,
+                    ))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    unsafe fn write_file(path: &std::ffi::CStr, data: &[u8]) -> io::Result<()> {
+        let fd = check(libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC))?;
+        let written = libc::write(fd, data.as_ptr().cast(), data.len());
+        libc::close(fd);
+        if written != data.len() as isize {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    // 只读重新挂载时须保留源挂载点上不可清除的标志
+    fn read_only_flags(path: &Path) -> io::Result<c_ulong> {
+        let path = cstring(path)?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        check(unsafe { libc::statvfs(path.as_ptr(), &mut stat) })?;
+        let mut flags = libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY;
+        for (st, ms) in [
+            (libc::ST_NOSUID, libc::MS_NOSUID),
+            (libc::ST_NODEV, libc::MS_NODEV),
+            (libc::ST_NOEXEC, libc::MS_NOEXEC),
+            (libc::ST_NOATIME, libc::MS_NOATIME),
+            (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+            (libc::ST_RELATIME, libc::MS_RELATIME),
+        ] {
+            if stat.f_flag & st != 0 {
+                flags |= ms;
+            }
+        }
+        Ok(flags)
+    }
+
+    // 架构不符、x32 调用和 BLOCKED 中的系统调用终止进程；
+    // clone3 的参数无法检查，返回 ENOSYS 使 C 库退回到 clone；
+    // clone 创建命名空间、socket 创建 IPv4/IPv6 套接字时终止进程
+    fn seccomp_filter() -> Vec<sock_filter> {
+        let load = |offset| stmt(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS, offset);
+        let kill = stmt(libc::BPF_RET | libc::BPF_K, libc::SECCOMP_RET_KILL_PROCESS);
+        let allow = stmt(libc::BPF_RET | libc::BPF_K, libc::SECCOMP_RET_ALLOW);
+        let equal = |value, jt, jf| jump(libc::BPF_JEQ, value, jt, jf);
+
+        let mut filter = vec![
+            load(DATA_ARCH),
+            equal(AUDIT_ARCH, 1, 0),
+            kill,
+            load(DATA_NR),
+        ];
+        #[cfg(target_arch = "x86_64")]
+        filter.extend([jump(libc::BPF_JGE, 0x4000_0000, 0, 1), kill]);
+        for &nr in BLOCKED {
+            filter.extend([equal(nr as u32, 0, 1), kill]);
+        }
+        filter.extend([
+            equal(libc::SYS_clone3 as u32, 0, 1),
+            stmt(
+                libc::BPF_RET | libc::BPF_K,
+                libc::SECCOMP_RET_ERRNO | libc::ENOSYS as u32,
+            ),
+        ]);
+        // x86_64 上 clone 的第一个参数是 flags，aarch64 同样如此
+        filter.extend([
+            equal(libc::SYS_clone as u32, 0, 4),
+            load(DATA_ARG0),
+            jump(libc::BPF_JSET, NAMESPACE_FLAGS as u32, 1, 0),
+            allow,
+            kill,
+        ]);
+        filter.extend([
+            equal(libc::SYS_socket as u32, 0, 5),
+            load(DATA_ARG0),
+            equal(libc::AF_INET as u32, 2, 0),
+            equal(libc::AF_INET6 as u32, 1, 0),
+            allow,
+            kill,
+        ]);
+        filter.push(allow);
+        filter
+    }
+
+    fn stmt(code: u32, k: u32) -> sock_filter {
+        sock_filter {
+            code: code as u16,
+            jt: 0,
+            jf: 0,
+            k,
+        }
+    }
+
+    fn jump(condition: u32, k: u32, jt: u8, jf: u8) -> sock_filter {
+        sock_filter {
+            code: (libc::BPF_JMP | condition | libc::BPF_K) as u16,
+            jt,
+            jf,
+            k,
+        }
+    }
+
+    fn cstring(path: &Path) -> io::Result<CString> {
+        CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+
+    fn check<T: Default + PartialOrd>(result: T) -> io::Result<T> {
+        if result < T::default() {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+mod imp {
+    use super::Sandbox;
+    use std::io;
+    use std::process::{Command, ExitStatus};
+
+    pub const SUPPORTED: bool = false;
+
+    pub fn apply(_sandbox: &Sandbox, _command: &mut Command) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "sandbox is not supported",
+        ))
+    }
+
+    pub fn violation(_status: &ExitStatus) -> Option<String> {
+        None
+    }
+}
@@ -0,0 +1,362 @@
+use crate::config::{find_exercise, ExerciseConfig};
+use crate::evaluator::{Evaluator, Progress};
+use crate::html::escape_html;
+use crate::i18n::{Lang, Messages};
+use crate::report::Report;
+use serde_json::json;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 本地结果服务器：
+//   GET  /                 结果页面
+//   GET  /report           当前报告（JSON）
+//   GET  /events           评测进度（server-sent events）
+//   POST /evaluate/<name>  重新评测一道习题
+pub struct Server {
+    listener: TcpListener,
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    root: PathBuf,
+    config: ExerciseConfig,
+    // 评测信息和服务器日志使用的语言
+    msg: Messages,
+    report: Mutex<Report>,
+    // 同一时间只评测一道习题
+    busy: AtomicBool,
+    subscribers: Mutex<Vec<Sender<String>>>,
+}
+
+impl Server {
+    // 绑定地址，并读取 root 下已有的 report.json 作为初始报告
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        root: impl Into<PathBuf>,
+        config: ExerciseConfig,
+    ) -> io::Result<Self> {
+        let root = root.into();
+        let report = Report::load(root.join("report.json")).unwrap_or_default();
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            shared: Arc::new(Shared {
+                root,
+                config,
+                msg: Messages::default(),
+                report: Mutex::new(report),
+                busy: AtomicBool::new(false),
+                subscribers: Mutex::new(Vec::new()),
+            }),
+        })
+    }
+
+    // 设置评测信息使用的语言，须在 run 之前调用
+    pub fn lang(mut self, lang: Lang) -> Self {
+        if let Some(shared) = Arc::get_mut(&mut self.shared) {
+            shared.msg = Messages::new(lang);
+        }
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    // 处理请求直到进程退出，每个连接使用一个线程
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("{}", self.shared.msg.format("serve.accept_failed", &[&e]));
+                    continue;
+                }
+            };
+            let shared = Arc::clone(&self.shared);
+            thread::spawn(move || {
+                // 客户端提前断开时写入失败，无需处理
+                let _ = handle_connection(&shared, stream);
+            });
+        }
+        Ok(())
+    }
+}
+
+fn handle_connection(shared: &Arc<Shared>, stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // 只保留请求头，不需要请求体，读到空行为止
+    let mut headers = Vec::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default();
+
+    match (method, path) {
+        ("GET", "/") => respond(stream, "200 OK", "text/html; charset=utf-8", &index_page(&shared.config)),
+        ("GET", "/report") => {
+            let body = serde_json::to_string_pretty(&*shared.report.lock().unwrap())?;
+            respond(stream, "200 OK", "application/json", &body)
+        }
+        ("GET", "/events") => stream_events(shared, stream),
+        ("POST", _) if !same_origin(&stream.local_addr()?, &headers) => respond(
+            stream,
+            "403 Forbidden",
+            "text/plain; charset=utf-8",
+            "cross-origin request rejected",
+        ),
+        ("POST", _) if path.starts_with("/evaluate/") => {
+            let name = percent_decode(&path["/evaluate/".len()..]);
+            let (status, message) = start_evaluation(shared, &name);
+            let body = json!({ "message": message }).to_string();
+            respond(stream, status, "application/json", &body)
+        }
+        _ => respond(stream, "404 Not Found", "text/plain; charset=utf-8", "not found"),
+    }
+}
+
+// 其他网页可以向本地端口发送请求：Host 必须是服务器自身的地址，浏览器附带的 Origin 也必须与之一致
+fn same_origin(local: &SocketAddr, headers: &[(String, String)]) -> bool {
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let Some(host) = header("host") else {
+        return false;
+    };
+    let port = local.port();
+    let own_host = [
+        local.to_string(),
+        format!("localhost:{}", port),
+        format!("127.0.0.1:{}", port),
+        format!("[::1]:{}", port),
+    ]
+    .iter()
+    .any(|own| own.eq_ignore_ascii_case(host));
+    own_host
+        && header("origin").is_none_or(|origin| {
+            origin.eq_ignore_ascii_case(&format!("http://{}", host))
+        })
+}
+
+fn respond(mut stream: TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// 保持连接，把评测进度逐条推送给浏览器
+fn stream_events(shared: &Shared, mut stream: TcpStream) -> io::Result<()> {
+    let (sender, receiver): (Sender<String>, Receiver<String>) = mpsc::channel();
+    shared.subscribers.lock().unwrap().push(sender);
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n"
+    )?;
+    stream.flush()?;
+    loop {
+        // 定期发送注释行，及时发现已断开的连接
+        let event = match receiver.recv_timeout(Duration::from_secs(15)) {
+            Ok(event) => event,
+            Err(mpsc::RecvTimeoutError::Timeout) => ": keep-alive\n\n".to_string(),
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+        stream.write_all(event.as_bytes())?;
+        stream.flush()?;
+    }
+}
+
+fn broadcast(shared: &Shared, event: &str, data: serde_json::Value) {
+    let message = format!("event: {}\ndata: {}\n\n", event, data);
+    shared
+        .subscribers
+        .lock()
+        .unwrap()
+        .retain(|subscriber| subscriber.send(message.clone()).is_ok());
+}
+
+// 离开作用域时清除忙碌标记，评测过程中 panic 也不会让服务器一直拒绝新的评测
+struct BusyGuard<'a>(&'a AtomicBool);
+
+impl Drop for BusyGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+// 在后台线程评测一道习题，完成后更新并保存报告
+fn start_evaluation(shared: &Arc<Shared>, name: &str) -> (&'static str, String) {
+    let exercises = shared.config.all_exercises();
+    let exercise = match find_exercise(&exercises, name) {
+        Some(exercise) => exercise.clone(),
+        None => return ("404 Not Found", format!("Unknown exercise: {}", name)),
+    };
+    if shared.busy.swap(true, Ordering::SeqCst) {
+        return ("409 Conflict", "Another exercise is being evaluated".to_string());
+    }
+
+    let shared = Arc::clone(shared);
+    let message = format!("Evaluating {}", exercise.name);
+    thread::spawn(move || {
+        let _busy = BusyGuard(&shared.busy);
+        let start_time = Instant::now();
+        let events = Arc::clone(&shared);
+        let mut evaluator = Evaluator::new(&shared.root, &shared.config)
+            .lang(shared.msg.lang())
+            .on_progress(move |event| match event {
+                Progress::Started { exercise } => {
+                    broadcast(&events, "started", json!({ "exercise": exercise.name }))
+                }
+                Progress::Output { exercise, line } => broadcast(
+                    &events,
+                    "output",
+                    json!({ "exercise": exercise.name, "line": line }),
+                ),
+                Progress::Finished { exercise, result } => broadcast(
+                    &events,
+                    "finished",
+                    json!({ "exercise": exercise.name, "result": result }),
+                ),
+            });
+        let result = evaluator.evaluate(&exercise);
+
+        {
+            let mut report = shared.report.lock().unwrap();
+            let elapsed = Duration::from_secs(report.statistics.total_time) + start_time.elapsed();
+            report.replace(result);
+            report.finish(&shared.config, elapsed);
+            // 重新评测后原签名不再有效
+            report.signature = None;
+            if let Err(e) = report.save(shared.root.join("report.json")) {
+                eprintln!("{}", shared.msg.format("report.save_failed", &[&e]));
+            }
+            broadcast(&shared, "report", json!(report.statistics));
+        }
+    });
+    ("202 Accepted", message)
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn index_page(config: &ExerciseConfig) -> String {
+    let mut rows = String::new();
+    for (category, exercises) in config.category_exercises() {
+        for exercise in exercises {
+            let name = escape_html(&exercise.name);
+            rows.push_str(&format!(
+                "<tr data-name=\"{0}\"><td>{1}</td><td>{0}</td><td>{2}</td>\
+                 <td class=\"status\">-</td><td class=\"score\">-</td>\
+                 <td><button class=\"evaluate\">Re-run</button></td></tr>\n",
+                name,
+                category,
+                escape_html(&exercise.exercise_type)
+            ));
+        }
+    }
+    INDEX_TEMPLATE.replace("{rows}", &rows)
+}
+
+const INDEX_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>cargotest results</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 4px 10px; }
+.pass { color: #080; } .fail { color: #c00; }
+#log { background: #111; color: #ddd; padding: 1em; height: 20em; overflow: auto; white-space: pre-wrap; }
+</style>
+</head>
+<body>
+<h1>cargotest results</h1>
+<p id="summary"></p>
+<table>
+<tr><th>Category</th><th>Exercise</th><th>Type</th><th>Result</th><th>Score</th><th></th></tr>
+{rows}</table>
+<h2>Progress</h2>
+<div id="log"></div>
+<script>
+function show(report) {
+  const s = report.statistics;
+  document.getElementById('summary').textContent =
+    `Score ${s.total_score}, passed ${s.total_successes}/${s.total_exercises}`;
+  for (const r of report.exercises) update(r.name, r);
+}
+function update(name, r) {
+  const row = document.querySelector(`tr[data-name="${CSS.escape(name)}"]`);
+  if (!row) return;
+  row.querySelector('.status').textContent = r.result ? 'PASSED' : 'FAILED';
+  row.querySelector('.status').className = 'status ' + (r.result ? 'pass' : 'fail');
+  row.querySelector('.score').textContent = r.score;
+}
+function log(line) {
+  const el = document.getElementById('log');
+  el.textContent += line.replace(/\x1b\[[0-9;]*m/g, '') + '\n';
+  el.scrollTop = el.scrollHeight;
+}
+function refresh() { fetch('/report').then(r => r.json()).then(show); }
+function evaluate(name) {
+  fetch('/evaluate/' + encodeURIComponent(name), { method: 'POST' })
+    .then(r => r.json()).then(r => log(r.message));
+}
+// 习题名从所在行的 data-name 读取，不拼接进脚本
+document.addEventListener('click', e => {
+  const button = e.target.closest('button.evaluate');
+  if (button) evaluate(button.closest('tr').dataset.name);
+});
+const events = new EventSource('/events');
+events.addEventListener('started', e => log('Evaluating ' + JSON.parse(e.data).exercise));
+events.addEventListener('output', e => log(JSON.parse(e.data).line));
+events.addEventListener('finished', e => { const d = JSON.parse(e.data); update(d.exercise, d.result); });
+events.addEventListener('report', refresh);
+refresh();
+</script>
+</body>
+</html>
+"#;
@@ -0,0 +1,92 @@
+use crate::config::{Exercise, ExerciseConfig};
+use crate::files::{relative_files, resolve};
+use crate::hint;
+use crate::report::ExerciseResult;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+// 保存已评测习题的结果，相对于仓库根目录
+const STATE_FILE: &str = ".cargotest/session.json";
+
+// 跨多次运行保存的评测进度，用于 watch 和 all --resume 从上次停下的位置继续
+pub struct SessionState {
+    path: PathBuf,
+    state: StoredState,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct StoredState {
+    // 配置文件的哈希，配置变化后全部结果失效
+    config: String,
+    results: BTreeMap<String, SavedResult>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedResult {
+    // 评测后习题文件及已查看提示层数的哈希
+    fingerprint: String,
+    result: ExerciseResult,
+}
+
+impl SessionState {
+    // 读取保存的进度；文件不存在、无法解析或配置已变化时从空进度开始
+    pub fn load(root: &Path, config: &ExerciseConfig) -> io::Result<Self> {
+        let path = root.join(STATE_FILE);
+        let config_hash = hex::encode(Sha256::digest(serde_json::to_vec(config)?));
+        let state = File::open(&path)
+            .ok()
+            .and_then(|file| serde_json::from_reader::<_, StoredState>(file).ok())
+            .filter(|state| state.config == config_hash)
+            .unwrap_or_else(|| StoredState {
+                config: config_hash,
+                results: BTreeMap::new(),
+            });
+        Ok(Self { path, state })
+    }
+
+    // 上次评测的结果，习题文件或提示使用情况变化后返回 None
+    pub fn lookup(&self, root: &Path, exercise: &Exercise) -> Option<&ExerciseResult> {
+        let saved = self.state.results.get(&exercise.name)?;
+        let current = fingerprint(root, exercise).ok()?;
+        (saved.fingerprint == current).then_some(&saved.result)
+    }
+
+    // 记录一道题的评测结果并立即写入文件，中途退出也不会丢失
+    pub fn store(
+        &mut self,
+        root: &Path,
+        exercise: &Exercise,
+        result: &ExerciseResult,
+    ) -> io::Result<()> {
+        self.state.results.insert(
+            exercise.name.clone(),
+            SavedResult {
+                fingerprint: fingerprint(root, exercise)?,
+                result: result.clone(),
+            },
+        );
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = File::create(&self.path)?;
+        serde_json::to_writer_pretty(file, &self.state)?;
+        Ok(())
+    }
+}
+
+fn fingerprint(root: &Path, exercise: &Exercise) -> io::Result<String> {
+    let source = exercise.source_path(root);
+    let mut hasher = Sha256::new();
+    for rel in relative_files(&source)? {
+        hasher.update(rel.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(fs::read(resolve(&source, &rel))?);
+        hasher.update([0]);
+    }
+    hasher.update(hint::hints_used(root, &exercise.name).to_le_bytes());
+    Ok(hex::encode(hasher.finalize()))
+}
@@ -0,0 +1,205 @@
+use crate::config::ExerciseConfig;
+use crate::environment::{command_output, unix_time};
+use crate::files::{relative_files, resolve};
+use crate::report::Report;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const ALGORITHM: &str = "hmac-sha256";
+
+// 默认的签名密钥文件，相对于仓库根目录；可用环境变量 CARGOTEST_SIGNING_KEY 指定其他路径
+const DEFAULT_KEY_FILE: &str = ".cargotest/signing.key";
+
+// 报告签名，value 为对其余全部内容（value 置空）规范化序列化后计算的 HMAC
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportSignature {
+    pub algorithm: String,
+    // 密钥 SHA-256 的前 16 位十六进制，用于区分密钥
+    pub key_id: String,
+    pub signed_at: u64,
+    pub environment: SigningEnvironment,
+    // 习题文件相对于仓库根目录的路径及其 SHA-256
+    pub sources: BTreeMap<String, String>,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SigningEnvironment {
+    pub os: String,
+    pub arch: String,
+    pub rustc: String,
+    pub cargotest: String,
+}
+
+// 校验结果
+#[derive(Debug)]
+pub struct Verification {
+    pub signature_valid: bool,
+    pub key_matches: bool,
+    // 与签名时内容不同、缺失或新增的文件
+    pub changed_sources: Vec<String>,
+}
+
+impl Verification {
+    pub fn is_valid(&self) -> bool {
+        self.signature_valid && self.key_matches && self.changed_sources.is_empty()
+    }
+}
+
+// 读取签名密钥；未配置密钥时返回 None
+pub fn load_key(root: &Path) -> io::Result<Option<Vec<u8>>> {
+    let path = match std::env::var_os("CARGOTEST_SIGNING_KEY") {
+        Some(path) => PathBuf::from(path),
+        None => root.join(DEFAULT_KEY_FILE),
+    };
+    match fs::read(&path) {
+        Ok(key) if key.is_empty() => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("signing key {} is empty", path.display()),
+        )),
+        Ok(key) => Ok(Some(key)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 记录习题源码哈希和环境信息后对报告签名
+pub fn sign(report: &mut Report, root: &Path, config: &ExerciseConfig, key: &[u8]) -> io::Result<()> {
+    report.signature = Some(ReportSignature {
+        algorithm: ALGORITHM.to_string(),
+        key_id: key_id(key),
+        signed_at: unix_time(),
+        environment: SigningEnvironment {
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            rustc: command_output(root, "rustc", &["--version"])
+                .unwrap_or_else(|| "unknown".to_string()),
+            cargotest: env!("CARGO_PKG_VERSION").to_string(),
+        },
+        sources: source_hashes(root, config)?,
+        value: String::new(),
+    });
+    let value = compute_mac(report, key)?;
+    if let Some(signature) = report.signature.as_mut() {
+        signature.value = value;
+    }
+    Ok(())
+}
+
+// 校验签名，并检查记录的源码哈希与当前工作区是否一致
+pub fn verify(report: &Report, root: &Path, config: &ExerciseConfig, key: &[u8]) -> io::Result<Verification> {
+    let signature = report.signature.as_ref().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "report is not signed")
+    })?;
+    if signature.algorithm != ALGORITHM {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported signature algorithm {}", signature.algorithm),
+        ));
+    }
+
+    let signature_valid = match hex::decode(&signature.value) {
+        Ok(expected) => {
+            let mut mac = HmacSha256::new_from_slice(key).map_err(io::Error::other)?;
+            mac.update(unsigned_payload(report)?.as_bytes());
+            mac.verify_slice(&expected).is_ok()
+        }
+        Err(_) => false,
+    };
+
+    let current = source_hashes(root, config)?;
+    let mut changed_sources: Vec<String> = signature
+        .sources
+        .iter()
+        .filter(|(path, hash)| current.get(*path) != Some(*hash))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed_sources.extend(
+        current
+            .keys()
+            .filter(|path| !signature.sources.contains_key(*path))
+            .cloned(),
+    );
+    changed_sources.sort();
+
+    Ok(Verification {
+        signature_valid,
+        key_matches: signature.key_id == key_id(key),
+        changed_sources,
+    })
+}
+
+fn compute_mac(report: &Report, key: &[u8]) -> io::Result<String> {
+    let mut mac = HmacSha256::new_from_slice(key).map_err(io::Error::other)?;
+    mac.update(unsigned_payload(report)?.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+// 签名值置空后的规范化 JSON
+fn unsigned_payload(report: &Report) -> io::Result<String> {
+    let mut value = serde_json::to_value(report)?;
+    if let Some(signature) = value.get_mut("signature").and_then(Value::as_object_mut) {
+        signature.insert("value".to_string(), Value::String(String::new()));
+    }
+    let mut out = String::new();
+    write_canonical(&value, &mut out);
+    Ok(out)
+}
+
+// 对象按键排序、无多余空白的 JSON，保证同一内容得到同一字节序列
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+// 计算所有习题文件的 SHA-256，键为相对于仓库根目录的路径
+fn source_hashes(root: &Path, config: &ExerciseConfig) -> io::Result<BTreeMap<String, String>> {
+    let mut hashes = BTreeMap::new();
+    for exercise in config.all_exercises() {
+        let source = exercise.source_path(root);
+        let display_root = Path::new("exercises").join(&exercise.path);
+        for rel in relative_files(&source)? {
+            let contents = fs::read(resolve(&source, &rel))?;
+            let key = resolve(&display_root, &rel).to_string_lossy().replace('\\', "/");
+            hashes.insert(key, hex::encode(Sha256::digest(&contents)));
+        }
+    }
+    Ok(hashes)
+}
+
+fn key_id(key: &[u8]) -> String {
+    hex::encode(Sha256::digest(key))[..16].to_string()
+}
@@ -0,0 +1,271 @@
+use crate::config::Exercise;
+use crate::constraints::is_test;
+use crate::files::{relative_files, resolve};
+use crate::reset;
+use crate::html::escape_html;
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+// 指纹使用的连续词法单元个数
+const WINDOW: usize = 5;
+
+// Rust 关键字保留原样，其余标识符按出现顺序重命名
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+// 一对相似的提交
+#[derive(Debug, Clone)]
+pub struct SimilarPair {
+    pub first: String,
+    pub second: String,
+    // Jaccard 相似度，0 到 1
+    pub score: f64,
+}
+
+// 某道习题中相似度不低于阈值的提交对，按相似度从高到低排列
+#[derive(Debug, Clone)]
+pub struct ExerciseSimilarity {
+    pub exercise: String,
+    // 参与比较的提交数
+    pub submissions: usize,
+    pub pairs: Vec<SimilarPair>,
+}
+
+// 去掉注释、空白、文档注释和测试代码，并把标识符按首次出现的顺序重命名为 v0、v1……
+pub fn normalize(text: &str) -> io::Result<Vec<String>> {
+    let stream = match syn::parse_file(text) {
+        Ok(mut file) => {
+            strip_tests(&mut file.items);
+            file.into_token_stream()
+        }
+        // 无法解析为完整语法树时退回到只做词法分析
+        Err(_) => TokenStream::from_str(text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?,
+    };
+    let mut tokens = Vec::new();
+    flatten(stream, &mut HashMap::new(), &mut tokens);
+    Ok(tokens)
+}
+
+// 两份提交的相似度
+pub fn similarity(first: &HashSet<u64>, second: &HashSet<u64>) -> f64 {
+    let union = first.union(second).count();
+    if union == 0 {
+        return 0.0;
+    }
+    first.intersection(second).count() as f64 / union as f64
+}
+
+// 比较 dir 下各学生仓库（dir/<学生>/exercises/...）中同一道习题的解答
+// 仓库根目录下记录了原始模板时，与模板相同的代码不计入相似度
+pub fn compare_submissions(
+    root: &Path,
+    dir: &Path,
+    exercises: &[Exercise],
+    threshold: f64,
+) -> io::Result<Vec<ExerciseSimilarity>> {
+    let mut students: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("exercises").is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    students.sort();
+
+    let mut results = Vec::new();
+    for exercise in exercises {
+        let template = match fingerprint(&reset::template_path(root, exercise)) {
+            Ok(Some(template)) => template,
+            _ => HashSet::new(),
+        };
+
+        let mut submissions = Vec::new();
+        for student in &students {
+            let source = exercise.source_path(&dir.join(student));
+            // 无法读取或解析的提交不参与比较
+            if let Ok(Some(prints)) = fingerprint(&source) {
+                let own: HashSet<u64> = prints.difference(&template).copied().collect();
+                if !own.is_empty() {
+                    submissions.push((student, own));
+                }
+            }
+        }
+
+        let mut pairs = Vec::new();
+        for (i, (first, a)) in submissions.iter().enumerate() {
+            for (second, b) in &submissions[i + 1..] {
+                let score = similarity(a, b);
+                if score >= threshold {
+                    pairs.push(SimilarPair {
+                        first: first.to_string(),
+                        second: second.to_string(),
+                        score,
+                    });
+                }
+            }
+        }
+        pairs.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.push(ExerciseSimilarity {
+            exercise: exercise.name.clone(),
+            submissions: submissions.len(),
+            pairs,
+        });
+    }
+    Ok(results)
+}
+
+// 每个习题每对提交一行，同一习题内按相似度排序
+pub fn to_csv(results: &[ExerciseSimilarity]) -> String {
+    let mut csv = String::from("exercise,first,second,similarity\n");
+    for result in results {
+        for pair in &result.pairs {
+            csv.push_str(&format!(
+                "{},{},{},{:.3}\n",
+                csv_field(&result.exercise),
+                csv_field(&pair.first),
+                csv_field(&pair.second),
+                pair.score
+            ));
+        }
+    }
+    csv
+}
+
+// 每个存在相似提交的习题一张表
+pub fn to_html(results: &[ExerciseSimilarity], threshold: f64) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Similarity report</title>\n\
+         <style>body { font-family: sans-serif; } table { border-collapse: collapse; margin-bottom: 1.5em; } \
+         td, th { border: 1px solid #ccc; padding: 4px 10px; } td.score { text-align: right; }</style>\n\
+         </head>\n<body>\n",
+    );
+    html.push_str(&format!(
+        "<h1>Similarity report</h1>\n<p>Pairs with similarity of at least {:.0}%.</p>\n",
+        threshold * 100.0
+    ));
+    for result in results.iter().filter(|r| !r.pairs.is_empty()) {
+        html.push_str(&format!(
+            "<h2>{}</h2>\n<p>{} submissions compared</p>\n<table>\n<tr><th>First</th><th>Second</th><th>Similarity</th></tr>\n",
+            escape_html(&result.exercise),
+            result.submissions
+        ));
+        for pair in &result.pairs {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td class=\"score\">{:.1}%</td></tr>\n",
+                escape_html(&pair.first),
+                escape_html(&pair.second),
+                pair.score * 100.0
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+// 习题源码的 k-gram 指纹；Cargo 项目合并 src 目录下全部 .rs 文件，不存在时返回 None
+fn fingerprint(source: &Path) -> io::Result<Option<HashSet<u64>>> {
+    if !source.exists() {
+        return Ok(None);
+    }
+    let mut tokens = Vec::new();
+    for rel in relative_files(source)? {
+        let is_source = rel.as_os_str().is_empty()
+            || (rel.starts_with("src") && rel.extension().is_some_and(|ext| ext == "rs"));
+        if is_source {
+            tokens.extend(normalize(&fs::read_to_string(resolve(source, &rel))?)?);
+        }
+    }
+    Ok(Some(
+        tokens
+            .windows(WINDOW)
+            .map(|window| {
+                let mut hasher = DefaultHasher::new();
+                window.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect(),
+    ))
+}
+
+fn strip_tests(items: &mut Vec<syn::Item>) {
+    items.retain(|item| match item {
+        syn::Item::Fn(function) => !is_test(&function.attrs),
+        syn::Item::Mod(module) => !is_test(&module.attrs),
+        _ => true,
+    });
+    for item in items {
+        if let syn::Item::Mod(module) = item {
+            if let Some((_, items)) = &mut module.content {
+                strip_tests(items);
+            }
+        }
+    }
+}
+
+fn flatten(stream: TokenStream, names: &mut HashMap<String, usize>, tokens: &mut Vec<String>) {
+    for tree in stream {
+        match tree {
+            // 文档注释在词法分析后成为 #[doc = "..."] 或 #![doc = "..."] 属性，一并去掉
+            TokenTree::Group(group)
+                if group.delimiter() == Delimiter::Bracket && is_doc(group.stream()) =>
+            {
+                if tokens.last().is_some_and(|t| t == "!") {
+                    tokens.pop();
+                }
+                if tokens.last().is_some_and(|t| t == "#") {
+                    tokens.pop();
+                }
+            }
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                if !open.is_empty() {
+                    tokens.push(open.to_string());
+                }
+                flatten(group.stream(), names, tokens);
+                if !close.is_empty() {
+                    tokens.push(close.to_string());
+                }
+            }
+            TokenTree::Ident(ident) => {
+                let name = ident.to_string();
+                if KEYWORDS.contains(&name.as_str()) {
+                    tokens.push(name);
+                } else {
+                    let next = names.len();
+                    tokens.push(format!("v{}", names.entry(name).or_insert(next)));
+                }
+            }
+            TokenTree::Punct(punct) => tokens.push(punct.as_char().to_string()),
+            TokenTree::Literal(literal) => tokens.push(literal.to_string()),
+        }
+    }
+}
+
+fn is_doc(attribute: TokenStream) -> bool {
+    matches!(attribute.into_iter().next(), Some(TokenTree::Ident(ident)) if ident == "doc")
+}
+
+// 含逗号、引号或换行的字段加引号
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
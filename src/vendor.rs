@@ -0,0 +1,153 @@
+use crate::config::Exercise;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Cargo 配置文件，相对于仓库根目录
+pub const CONFIG_FILE: &str = ".cargo/config.toml";
+// 默认的依赖源码目录
+pub const DEFAULT_DIR: &str = "vendor";
+
+// 离线构建检查使用的空 Cargo 目录，相对于仓库根目录
+const PREFLIGHT_HOME: &str = ".cargotest/preflight";
+
+// 使用本地源码的配置中必有的一行，已存在时不再写入
+const REPLACE_WITH: &str = "replace-with = \"vendored-sources\"";
+
+// 无法离线构建的习题及原因
+#[derive(Debug, Clone)]
+pub struct OfflineIssue {
+    pub exercise: String,
+    pub reason: String,
+}
+
+// 需要 Cargo 构建的习题（目录下有 Cargo.toml）及其清单文件
+pub fn cargo_exercises(root: &Path, exercises: &[Exercise]) -> Vec<(String, PathBuf)> {
+    exercises
+        .iter()
+        .map(|exercise| {
+            (
+                exercise.name.clone(),
+                exercise.source_path(root).join("Cargo.toml"),
+            )
+        })
+        .filter(|(_, manifest)| manifest.is_file())
+        .collect()
+}
+
+// 把评测程序本身和全部 Cargo 习题的依赖源码下载到 root/dir，并在 .cargo/config.toml 中配置 Cargo 使用这些源码；
+// offline 时只使用本机 Cargo 缓存中已有的包。返回下载的包数，没有任何依赖时不写入配置
+pub fn vendor(root: &Path, exercises: &[Exercise], dir: &str, offline: bool) -> io::Result<usize> {
+    let manifests: Vec<PathBuf> = cargo_exercises(root, exercises)
+        .into_iter()
+        .map(|(_, manifest)| relative(root, &manifest))
+        .collect();
+
+    let mut command = Command::new("cargo");
+    command
+        .arg("vendor")
+        .arg("--versioned-dirs")
+        .current_dir(root);
+    if offline {
+        command.arg("--offline");
+    }
+    // 仓库根目录不是 Cargo 项目时以第一道习题为主清单
+    let mut manifests = manifests.iter();
+    if !root.join("Cargo.toml").is_file() {
+        match manifests.next() {
+            Some(manifest) => command.arg("--manifest-path").arg(manifest),
+            None => return Ok(0),
+        };
+    }
+    for manifest in manifests {
+        command.arg("--sync").arg(manifest);
+    }
+    let output = command.arg(dir).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(cargo_error(&String::from_utf8_lossy(
+            &output.stderr,
+        ))));
+    }
+
+    // cargo vendor 在标准输出中给出需要加入配置的片段，没有依赖时不输出
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let Some(snippet) = stdout.find("[source.").map(|start| &stdout[start..]) else {
+        return Ok(0);
+    };
+    let config_path = root.join(CONFIG_FILE);
+    let existing = fs::read_to_string(&config_path).unwrap_or_default();
+    if !existing.contains(REPLACE_WITH) {
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let separator = if existing.is_empty() || existing.ends_with("\n\n") {
+            ""
+        } else {
+            "\n"
+        };
+        fs::write(
+            &config_path,
+            format!("{}{}{}", existing, separator, snippet.trim_end()) + "\n",
+        )?;
+    }
+
+    Ok(fs::read_dir(root.join(dir))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .count())
+}
+
+// 检查每道 Cargo 习题能否只用仓库中的依赖源码离线构建。使用空的 CARGO_HOME，
+// 本机 Cargo 缓存中已有的包不会掩盖缺失或未配置的源码目录
+pub fn preflight(root: &Path, exercises: &[Exercise]) -> io::Result<Vec<OfflineIssue>> {
+    let cargo_home = std::path::absolute(root.join(PREFLIGHT_HOME))?;
+    if cargo_home.exists() {
+        fs::remove_dir_all(&cargo_home)?;
+    }
+    fs::create_dir_all(&cargo_home)?;
+
+    let mut issues = Vec::new();
+    for (exercise, manifest) in cargo_exercises(root, exercises) {
+        // 在习题目录下运行，使仓库中的 .cargo/config.toml 生效
+        let dir = manifest.parent().unwrap_or(root);
+        let output = Command::new("cargo")
+            .args(["fetch", "--offline"])
+            .env("CARGO_HOME", &cargo_home)
+            .current_dir(dir)
+            .output()?;
+        if !output.status.success() {
+            issues.push(OfflineIssue {
+                exercise,
+                reason: cargo_error(&String::from_utf8_lossy(&output.stderr)),
+            });
+        }
+    }
+    Ok(issues)
+}
+
+// Cargo 错误输出中的第一条错误，后面附上“required by package”之类的说明
+fn cargo_error(stderr: &str) -> String {
+    let mut lines = stderr
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| !line.starts_with("error"));
+    let Some(first) = lines.next() else {
+        return stderr
+            .trim()
+            .lines()
+            .last()
+            .unwrap_or("cargo failed")
+            .to_string();
+    };
+    let first = first.trim_start_matches("error:").trim();
+    let detail = lines.find(|line| line.starts_with("required by package"));
+    match detail {
+        Some(detail) => format!("{} ({})", first, detail),
+        None => first.to_string(),
+    }
+}
+
+fn relative(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use cargotest::batch::grade_batch;
+use cargotest::ExerciseConfig;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// 改动了测试以掩盖错误的解答
+const TAMPERED: &str = r#"pub fn double(x: i32) -> i32 {
+    x * 3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double() {
+        assert_eq!(double(21), 63);
+    }
+}
+"#;
+
+const CORRECT: &str = "pub fn double(x: i32) -> i32 {\n    x << 1\n}\n";
+
+fn submit(dir: &Path, student: &str, exercise: &str, source: &str) {
+    let path = dir.join(student).join("exercises/easy").join(exercise);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, source).unwrap();
+}
+
+#[test]
+fn grades_each_student_with_trusted_tests() {
+    let root = common::fixture("basic");
+    let mut config = ExerciseConfig::load(root.path().join("exercise_config.json")).unwrap();
+    config.easy.truncate(2);
+    config.normal.clear();
+
+    let cohort = tempfile::tempdir().unwrap();
+    submit(cohort.path(), "alice", "pass.rs", TAMPERED);
+    submit(cohort.path(), "alice", "fail.rs", CORRECT);
+    submit(cohort.path(), "bob", "pass.rs", CORRECT);
+
+    let mut graded = Vec::new();
+    let gradebook = grade_batch(root.path(), cohort.path(), &config, 2, |grade| {
+        graded.push(grade.student.clone())
+    })
+    .unwrap();
+    graded.sort();
+    assert_eq!(graded, ["alice", "bob"]);
+
+    assert_eq!(gradebook.exercises, ["pass.rs", "fail.rs"]);
+    let scores: Vec<(&str, Vec<i32>, i32)> = gradebook
+        .students
+        .iter()
+        .map(|s| {
+            let scores = s.exercises.iter().map(|e| e.score).collect();
+            (s.student.as_str(), scores, s.total_score)
+        })
+        .collect();
+    assert_eq!(scores, [("alice", vec![0, 1], 1), ("bob", vec![1, 0], 1)]);
+
+    let alice_error = gradebook.students[0].exercises[0].error.as_deref().unwrap();
+    assert!(alice_error.starts_with("test failed"), "{}", alice_error);
+    let bob_error = gradebook.students[1].exercises[1].error.as_deref().unwrap();
+    assert!(bob_error.starts_with("compile failed"), "{}", bob_error);
+
+    // 可信测试中的断言使用标准库的完整路径，学生代码无法覆盖
+    let merged = root.path().join(".cargotest/batch/bob/exercises/easy/pass.rs");
+    assert!(fs::read_to_string(merged)
+        .unwrap()
+        .contains("::std::assert_eq!(double(21), 42);"));
+
+    let csv = gradebook.to_csv();
+    assert!(csv.starts_with("student,pass.rs,fail.rs,total,passed,errors\nalice,0,1,1,1,"));
+}
+
+// 把断言宏重新定义为空宏，使可信测试什么也不检查
+const SILENCED: &str = r#"pub fn double(x: i32) -> i32 {
+    x
+}
+
+macro_rules! assert_eq {
+    ($($t:tt)*) => {};
+}
+"#;
+
+// 以导入时重命名的方式覆盖断言宏
+const RENAMED: &str = r#"pub fn double(x: i32) -> i32 {
+    x
+}
+
+macro_rules! ignore {
+    ($($t:tt)*) => {};
+}
+pub(crate) use ignore as assert_eq;
+"#;
+
+#[test]
+fn rejects_submissions_that_redefine_assertions() {
+    let root = common::fixture("basic");
+    let mut config = ExerciseConfig::load(root.path().join("exercise_config.json")).unwrap();
+    config.easy.truncate(1);
+    config.normal.clear();
+
+    let cohort = tempfile::tempdir().unwrap();
+    submit(cohort.path(), "eve", "pass.rs", SILENCED);
+    submit(cohort.path(), "mallory", "pass.rs", RENAMED);
+    let gradebook = grade_batch(root.path(), cohort.path(), &config, 2, |_| {}).unwrap();
+
+    for student in &gradebook.students {
+        assert_eq!(student.total_score, 0, "{}", student.student);
+        let error = student.exercises[0].error.as_deref().unwrap();
+        assert!(
+            error.contains("redefines the standard macro `assert_eq!`"),
+            "{}",
+            error
+        );
+    }
+    let workspace = root.path().join(".cargotest/batch/eve/exercises/easy/pass.rs");
+    assert!(!fs::read_to_string(workspace)
+        .unwrap()
+        .contains("macro_rules"));
+}
+
+#[test]
+fn stops_runaway_submissions_and_applies_hint_penalty() {
+    let root = common::fixture("basic");
+    let mut config = ExerciseConfig::load(root.path().join("exercise_config.json")).unwrap();
+    config.easy.truncate(1);
+    config.normal.clear();
+    config.hint_penalty = 1;
+    config.timeout_secs = Some(2);
+
+    let cohort = tempfile::tempdir().unwrap();
+    submit(
+        cohort.path(),
+        "alice",
+        "pass.rs",
+        "pub fn double(x: i32) -> i32 {\n    loop {\n        std::hint::black_box(x);\n    }\n}\n",
+    );
+    submit(cohort.path(), "bob", "pass.rs", CORRECT);
+    let usage = cohort.path().join("bob/.cargotest/hints.json");
+    fs::create_dir_all(usage.parent().unwrap()).unwrap();
+    fs::write(usage, r#"{ "pass.rs": 1 }"#).unwrap();
+
+    let start = Instant::now();
+    let gradebook = grade_batch(root.path(), cohort.path(), &config, 2, |_| {}).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(60));
+
+    let alice = &gradebook.students[0].exercises[0];
+    assert!(!alice.passed);
+    assert_eq!(alice.error.as_deref(), Some("timed out"));
+    // 学生查看过提示，通过后按 hint_penalty 扣分
+    let bob = &gradebook.students[1].exercises[0];
+    assert!(bob.passed);
+    assert_eq!(bob.score, 0);
+}
@@ -0,0 +1,23 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+// 把夹具复制到临时目录，避免并行测试相互覆盖编译产物
+pub fn fixture(name: &str) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    copy_dir(&Path::new("tests/fixtures").join(name), dir.path());
+    dir
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+}
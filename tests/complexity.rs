@@ -0,0 +1,148 @@
+mod common;
+
+use cargotest::complexity::{estimate, Complexity, ComplexitySettings, Sample};
+use cargotest::{Evaluator, ExerciseConfig, Progress};
+use std::sync::{Arc, Mutex};
+
+fn samples(f: impl Fn(f64) -> f64) -> Vec<Sample> {
+    [1000u64, 2000, 4000, 8000, 16000, 32000]
+        .into_iter()
+        .map(|size| Sample {
+            size,
+            nanos: f(size as f64),
+        })
+        .collect()
+}
+
+#[test]
+fn estimates_growth_classes() {
+    assert_eq!(estimate(&samples(|_| 40.0)), Some(Complexity::Constant));
+    assert_eq!(
+        estimate(&samples(|n| 30.0 * n.ln())),
+        Some(Complexity::Logarithmic)
+    );
+    assert_eq!(estimate(&samples(|n| 3.0 * n)), Some(Complexity::Linear));
+    assert_eq!(
+        estimate(&samples(|n| 3.0 * n * n.ln())),
+        Some(Complexity::Linearithmic)
+    );
+    assert_eq!(
+        estimate(&samples(|n| n * n / 7.0)),
+        Some(Complexity::Quadratic)
+    );
+    assert_eq!(
+        estimate(&samples(|n| 1.6f64.powf(n / 1000.0))),
+        Some(Complexity::Exponential)
+    );
+    assert_eq!(estimate(&samples(|n| 3.0 * n)[..1]), None);
+}
+
+#[test]
+fn fails_exercises_slower_than_target() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let mut exercise = config.easy[0].clone();
+    exercise.complexity = Some(ComplexitySettings {
+        harness: "complexity/pass.rs".to_string(),
+        target: Complexity::Linear,
+        start: 100,
+        factor: 2,
+        steps: 4,
+        timeout_ms: 5000,
+        fail: true,
+    });
+
+    let result = Evaluator::new(dir.path(), &config).evaluate(&exercise);
+    assert!(!result.result);
+    let complexity = result.complexity.unwrap();
+    assert_eq!(complexity.target, Complexity::Linear);
+    assert_eq!(complexity.estimated, Complexity::Quadratic);
+    assert_eq!(complexity.samples.len(), 4);
+}
+
+// 在窗口中逐个查找重复字符，窗口长度为 k 时耗时为 O(n·k)
+const SCANNING_WINDOW: &str = r#"use std::collections::VecDeque;
+
+pub fn longest_substring_without_repeating_chars(s: String) -> i32 {
+    let mut window = VecDeque::new();
+    let mut longest = 0;
+    for c in s.chars() {
+        while window.contains(&c) {
+            window.pop_front();
+        }
+        window.push_back(c);
+        longest = longest.max(window.len());
+    }
+    longest as i32
+}
+"#;
+
+// 记录每个字符上次出现的位置，直接跳过重复字符
+const SLIDING_WINDOW: &str = r#"use std::collections::HashMap;
+
+pub fn longest_substring_without_repeating_chars(s: String) -> i32 {
+    let mut last_seen = HashMap::new();
+    let (mut start, mut longest) = (0, 0);
+    for (i, c) in s.chars().enumerate() {
+        if let Some(previous) = last_seen.insert(c, i) {
+            start = start.max(previous + 1);
+        }
+        longest = longest.max(i + 1 - start);
+    }
+    longest as i32
+}
+"#;
+
+#[test]
+fn flags_window_scan_in_longest_substring() {
+    let dir = common::fixture("basic");
+    let root = dir.path();
+    let config = ExerciseConfig::load("exercise_config.json").unwrap();
+    let exercise = config
+        .all_exercises()
+        .into_iter()
+        .find(|e| e.name == "algorithm15.rs")
+        .unwrap();
+    std::fs::copy(
+        "complexity/algorithm15.rs",
+        root.join("complexity/algorithm15.rs"),
+    )
+    .unwrap();
+    let evaluate = |source: &str| {
+        std::fs::write(root.join("exercises/easy/algorithm15.rs"), source).unwrap();
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&lines);
+        let result = Evaluator::new(root, &config)
+            .on_progress(move |event| {
+                if let Progress::Output { line, .. } = event {
+                    sink.lock().unwrap().push(line);
+                }
+            })
+            .evaluate(&exercise);
+        let lines = lines.lock().unwrap().clone();
+        (result, lines)
+    };
+
+    // 仓库中的解答逐字节在窗口中查找重复，输入中的字符都不是 ASCII，结果错误即判为不通过
+    let shipped = std::fs::read_to_string("exercises/easy/algorithm15.rs").unwrap();
+    let (result, lines) = evaluate(&shipped);
+    assert!(!result.result);
+    assert!(result.complexity.is_none());
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.contains("wrong result at size 1000")),
+        "{:?}",
+        lines
+    );
+
+    let (result, _) = evaluate(SLIDING_WINDOW);
+    assert!(result.result);
+    let estimated = result.complexity.unwrap().estimated;
+    assert!(estimated <= Complexity::Linear, "{}", estimated);
+
+    let (result, _) = evaluate(SCANNING_WINDOW);
+    assert!(!result.result);
+    let estimated = result.complexity.unwrap().estimated;
+    assert!(estimated >= Complexity::Quadratic, "{}", estimated);
+}
@@ -0,0 +1,226 @@
+mod common;
+
+use cargotest::constraints::{check_source, ConstraintSettings};
+use cargotest::{Context, Evaluator, ExerciseConfig, ExerciseEvaluator};
+use std::fs;
+
+const SOURCE: &str = r#"use std::collections::HashSet;
+
+pub fn get_sum(a: i32, b: i32) -> i32 {
+    let mut total = a;
+    total += b;
+    println!("{}", a + b);
+    total
+}
+
+pub fn dedup(nums: Vec<i32>) -> Vec<i32> {
+    let seen: HashSet<i32> = nums.iter().cloned().collect();
+    let mut out = Vec::new();
+    out.extend(seen);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn uses_plus() {
+        assert_eq!(1 + 1, 2);
+    }
+}
+"#;
+
+fn violations(settings: &ConstraintSettings) -> Vec<String> {
+    check_source(SOURCE, "src.rs", settings)
+        .unwrap()
+        .into_iter()
+        .map(|v| format!("{}:{} {}", v.line, v.column, v.message))
+        .collect()
+}
+
+#[test]
+fn reports_forbidden_code_with_positions() {
+    let settings = ConstraintSettings {
+        functions: vec!["get_sum".to_string()],
+        forbidden_operators: vec!["+".to_string(), "+=".to_string()],
+        ..ConstraintSettings::default()
+    };
+    assert_eq!(
+        violations(&settings),
+        [
+            "5:11 uses forbidden operator `+=`",
+            "6:22 uses forbidden operator `+`",
+        ]
+    );
+
+    let settings = ConstraintSettings {
+        forbidden_paths: vec!["HashSet".to_string(), "Vec::new".to_string()],
+        forbidden_calls: vec!["collect".to_string()],
+        ..ConstraintSettings::default()
+    };
+    assert_eq!(
+        violations(&settings),
+        [
+            "1:23 uses forbidden path `HashSet`",
+            "11:15 uses forbidden path `HashSet`",
+            "11:51 calls forbidden function `collect`",
+            "12:19 uses forbidden path `Vec::new`",
+        ]
+    );
+}
+
+#[test]
+fn fails_exercises_that_break_constraints() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let mut exercise = config.easy[0].clone();
+    exercise.constraints = Some(ConstraintSettings {
+        forbidden_operators: vec!["*".to_string()],
+        ..ConstraintSettings::default()
+    });
+
+    let result = Evaluator::new(dir.path(), &config).evaluate(&exercise);
+    assert!(!result.result);
+    assert_eq!(result.violations.len(), 1);
+    assert_eq!(result.violations[0].file, "exercises/easy/pass.rs");
+    assert_eq!(result.violations[0].line, 2);
+}
+
+#[test]
+fn checks_helpers_called_by_listed_functions() {
+    let source = r#"pub fn get_sum(a: i32, b: i32) -> i32 {
+    Adder::apply(a, b, helper)
+}
+
+fn helper(a: i32, b: i32) -> i32 {
+    println!("{}", nested(a));
+    a + b
+}
+
+fn nested(a: i32) -> i32 {
+    a + 0
+}
+
+struct Adder;
+
+impl Adder {
+    fn apply(a: i32, b: i32, f: fn(i32, i32) -> i32) -> i32 {
+        f(a, b)
+    }
+}
+
+fn unrelated(a: i32) -> i32 {
+    a + 1
+}
+
+#[cfg(test)]
+mod tests {
+    fn helper(a: i32) -> i32 {
+        a + 2
+    }
+}
+"#;
+    let settings = ConstraintSettings {
+        functions: vec!["get_sum".to_string()],
+        forbidden_operators: vec!["+".to_string()],
+        ..ConstraintSettings::default()
+    };
+    let lines: Vec<usize> = check_source(source, "src.rs", &settings)
+        .unwrap()
+        .into_iter()
+        .map(|v| v.line)
+        .collect();
+    assert_eq!(lines, [7, 11]);
+}
+
+#[test]
+fn checks_macros_used_by_listed_functions() {
+    let source = r#"macro_rules! add {
+    ($a:expr, $($rest:expr),+) => {
+        $a + add!($($rest),+)
+    };
+    ($a:expr) => {
+        $a
+    };
+}
+
+macro_rules! sorted {
+    ($v:expr) => {{
+        let mut v = $v;
+        v.sort();
+        std::collections::HashMap::<i32, i32>::new();
+        v
+    }};
+}
+
+macro_rules! unrelated {
+    ($a:expr) => {
+        $a + 1
+    };
+}
+
+pub fn get_sum(a: i32, b: i32) -> i32 {
+    add!(a, b)
+}
+
+pub fn get_sorted(v: Vec<i32>) -> Vec<i32> {
+    macro_rules! local {
+        ($a:expr) => {
+            $a + 0
+        };
+    }
+    local!(0);
+    sorted!(v)
+}
+"#;
+    let settings = ConstraintSettings {
+        functions: vec!["get_sum".to_string(), "get_sorted".to_string()],
+        forbidden_operators: vec!["+".to_string()],
+        forbidden_paths: vec!["HashMap".to_string()],
+        forbidden_calls: vec!["sort".to_string()],
+    };
+    let found: Vec<String> = check_source(source, "src.rs", &settings)
+        .unwrap()
+        .into_iter()
+        .map(|v| format!("{}:{} {}", v.line, v.column, v.message))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            "3:12 uses forbidden operator `+`",
+            "13:11 calls forbidden function `sort`",
+            "14:9 uses forbidden path `HashMap`",
+            "32:16 uses forbidden operator `+`",
+        ]
+    );
+}
+
+struct AlwaysPasses;
+
+impl ExerciseEvaluator for AlwaysPasses {
+    fn evaluate(&self, _ctx: &mut Context) -> bool {
+        true
+    }
+}
+
+#[test]
+fn fails_exercises_when_constraint_check_fails() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let mut exercise = config.easy[0].clone();
+    // 测试视为通过，但源码无法解析
+    exercise.exercise_type = "always".to_string();
+    fs::write(exercise.source_path(dir.path()), "pub fn double(\n").unwrap();
+    exercise.constraints = Some(ConstraintSettings {
+        forbidden_operators: vec!["*".to_string()],
+        ..ConstraintSettings::default()
+    });
+
+    let result = Evaluator::new(dir.path(), &config)
+        .register("always", AlwaysPasses)
+        .evaluate(&exercise);
+    assert!(!result.result);
+    assert_eq!(result.violations.len(), 1);
+    assert!(result.violations[0]
+        .message
+        .starts_with("constraint check failed"));
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use cargotest::batch::grade_batch;
+use cargotest::environment::{parse_version, Environment};
+use cargotest::{Evaluator, ExerciseConfig};
+use std::fs;
+
+#[test]
+fn compares_rustc_against_minimum_version() {
+    assert_eq!(parse_version("1.70"), Some((1, 70, 0)));
+    assert_eq!(parse_version("1.95.0-nightly"), Some((1, 95, 0)));
+    assert_eq!(parse_version("latest"), None);
+
+    let environment = Environment {
+        rustc: Some("rustc 1.75.0 (82e1608df 2023-12-21)".to_string()),
+        ..Environment::default()
+    };
+    assert_eq!(environment.rustc_release(), Some((1, 75, 0)));
+    assert!(environment.check_min_rustc("1.70.0").is_ok());
+    assert!(environment.check_min_rustc("1.75").is_ok());
+    let error = environment.check_min_rustc("1.80.0").unwrap_err();
+    assert!(
+        error.contains("older than the required rustc 1.80.0"),
+        "{}",
+        error
+    );
+    assert!(Environment::default().check_min_rustc("1.70").is_err());
+}
+
+#[test]
+fn refuses_to_evaluate_with_old_toolchain() {
+    let dir = common::fixture("basic");
+    let mut config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    config.easy.truncate(1);
+    config.normal.clear();
+    config.min_rustc = Some("999.0".to_string());
+
+    let mut evaluator = Evaluator::new(dir.path(), &config);
+    let error = evaluator.check_toolchain().unwrap_err();
+    assert!(
+        error.contains("older than the required rustc 999.0"),
+        "{}",
+        error
+    );
+    let report = evaluator.evaluate_all(&config);
+    assert!(!report.exercises[0].result);
+    assert!(report.exercises[0].logs.is_empty());
+
+    // 批量评测同样检查工具链
+    let cohort = tempfile::tempdir().unwrap();
+    let submitted = cohort.path().join("alice/exercises/easy");
+    fs::create_dir_all(&submitted).unwrap();
+    fs::copy(
+        config.easy[0].source_path(dir.path()),
+        submitted.join("pass.rs"),
+    )
+    .unwrap();
+    let gradebook = grade_batch(dir.path(), cohort.path(), &config, 1, |_| {}).unwrap();
+    let error = gradebook.students[0].error.as_deref().unwrap();
+    assert!(error.starts_with("refusing to grade"), "{}", error);
+    assert_eq!(gradebook.students[0].total_score, 0);
+}
@@ -0,0 +1,409 @@
+mod common;
+
+use cargotest::{
+    ConfigError, Context, Evaluator, EvaluatorRegistry, ExerciseConfig, ExerciseEvaluator, Progress,
+};
+use cargotest::leak::LeakCheck;
+use cargotest::logs::KEPT_RUNS;
+use common::fixture;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn load_config(root: &Path) -> ExerciseConfig {
+    ExerciseConfig::load(root.join("exercise_config.json")).unwrap()
+}
+
+#[test]
+fn loads_fixture_config() {
+    let dir = fixture("basic");
+    let config = load_config(dir.path());
+    let names: Vec<String> = config.all_exercises().into_iter().map(|e| e.name).collect();
+    assert_eq!(
+        names,
+        ["pass.rs", "fail.rs", "broken.rs", "leaky.rs", "clamp.rs", "sum"]
+    );
+}
+
+#[test]
+fn rejects_invalid_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("exercise_config.json");
+    fs::write(
+        &path,
+        r#"{
+            "easy": [
+                { "name": "a.rs", "path": "easy/a.rs", "type": "single_file", "score": 1 },
+                { "name": "a.rs", "path": "easy/b.rs", "type": "single_file", "score": 1 }
+            ],
+            "normal": [
+                { "name": "c", "path": "normal/c", "type": "makefile", "score": 6 }
+            ],
+            "hard": []
+        }"#,
+    )
+    .unwrap();
+
+    match ExerciseConfig::load(&path) {
+        Err(ConfigError::Invalid(problems)) => {
+            assert_eq!(problems.len(), 2, "{:?}", problems);
+            assert!(problems[0].contains("duplicate"));
+            assert!(problems[1].contains("makefile"));
+        }
+        other => panic!("expected validation error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn evaluates_fixture_exercises() {
+    let dir = fixture("basic");
+    let config = load_config(dir.path());
+    let report = Evaluator::new(dir.path(), &config).evaluate_all(&config);
+
+    let outcome: Vec<(&str, bool, i32)> = report
+        .exercises
+        .iter()
+        .map(|r| (r.name.as_str(), r.result, r.score))
+        .collect();
+    assert_eq!(
+        outcome,
+        [
+            ("pass.rs", true, 1),
+            ("fail.rs", false, 0),
+            ("broken.rs", false, 0),
+            ("leaky.rs", false, 0),
+            ("clamp.rs", false, 0),
+            ("sum", true, 5),
+        ]
+    );
+    assert_eq!(report.statistics.total_exercises, 6);
+    assert_eq!(report.statistics.total_successes, 2);
+    assert_eq!(report.statistics.total_score, 6);
+
+    let steps: Vec<(&str, bool)> = report.exercises[1]
+        .logs
+        .iter()
+        .map(|s| (s.step.as_str(), s.success))
+        .collect();
+    assert_eq!(steps, [("compile", true), ("test", false)]);
+    let test_log = &report.exercises[1].logs[1];
+    assert!(test_log.stdout.starts_with(".cargotest/logs/"));
+    let stdout = fs::read_to_string(dir.path().join(&test_log.stdout)).unwrap();
+    assert!(stdout.contains("FAILED"), "{}", stdout);
+
+    let sum_steps: Vec<&str> = report.exercises[5].logs.iter().map(|s| s.step.as_str()).collect();
+    assert_eq!(sum_steps, ["build", "case lines", "case small"]);
+    let (_, latest) = cargotest::logs::latest(dir.path(), "sum").unwrap().unwrap();
+    assert_eq!(latest.len(), 3);
+
+    let environment = report.environment.as_ref().unwrap();
+    assert!(environment.rustc.as_deref().unwrap().starts_with("rustc "));
+    assert!(environment.host.is_some());
+    assert!(environment.cpus >= 1);
+    assert!(environment.finished_at >= environment.started_at);
+
+    let leaky = &report.exercises[3];
+    assert_eq!(leaky.leaks.len(), 1);
+    assert_eq!(leaky.leaks[0].allocations, 1);
+
+    let counterexample = report.exercises[4].counterexample.as_ref().unwrap();
+    assert_eq!(counterexample.input, "-1");
+    assert_eq!(counterexample.expected, "0");
+    assert_eq!(counterexample.actual, "-1");
+}
+
+#[test]
+fn prunes_old_log_runs() {
+    let dir = fixture("basic");
+    let root = dir.path();
+    let config = load_config(root);
+    let runs_dir = root.join(".cargotest/logs");
+    for i in 0..KEPT_RUNS + 5 {
+        fs::create_dir_all(runs_dir.join(format!("{:013}", i)).join("pass.rs")).unwrap();
+    }
+
+    let mut evaluator = Evaluator::new(root, &config);
+    assert!(evaluator.evaluate(&config.easy[0]).result);
+    let mut runs: Vec<String> = fs::read_dir(&runs_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    runs.sort();
+    assert_eq!(runs.len(), KEPT_RUNS);
+    assert_eq!(runs[0], format!("{:013}", 6));
+    let (latest, _) = cargotest::logs::latest(root, "pass.rs").unwrap().unwrap();
+    assert_eq!(&latest, runs.last().unwrap());
+}
+
+#[test]
+fn reports_progress_events() {
+    let dir = fixture("basic");
+    let config = load_config(dir.path());
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+
+    let mut evaluator = Evaluator::new(dir.path(), &config).on_progress(move |event| {
+        let entry = match event {
+            Progress::Started { exercise } => format!("started {}", exercise.name),
+            Progress::Output { .. } => "output".to_string(),
+            Progress::Finished { exercise, result } => {
+                format!("finished {} {}", exercise.name, result.result)
+            }
+        };
+        sink.lock().unwrap().push(entry);
+    });
+    evaluator.evaluate(&config.easy[0]);
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.first().unwrap(), "started pass.rs");
+    assert_eq!(events.last().unwrap(), "finished pass.rs true");
+    assert!(events.iter().any(|e| e == "output"));
+}
+
+#[test]
+fn applies_hint_penalty() {
+    let dir = fixture("basic");
+    let mut config = load_config(dir.path());
+    config.hint_penalty = 1;
+    let exercise = config.easy[0].clone();
+    cargotest::hint::reveal_next_hint(dir.path(), &exercise).unwrap();
+
+    let result = Evaluator::new(dir.path(), &config).evaluate(&exercise);
+    assert!(result.result);
+    assert_eq!(result.hints_used, 1);
+    assert_eq!(result.score, 0);
+}
+
+struct NameLength;
+
+impl ExerciseEvaluator for NameLength {
+    fn evaluate(&self, ctx: &mut Context) -> bool {
+        ctx.log("checking name");
+        ctx.exercise().name.len() > 3
+    }
+}
+
+#[test]
+fn dispatches_custom_exercise_types() {
+    let dir = fixture("basic");
+    let path = dir.path().join("custom_config.json");
+    fs::write(
+        &path,
+        r#"{
+            "easy": [
+                { "name": "exists", "path": "easy/pass.rs", "type": "file_exists", "score": 1 },
+                { "name": "missing", "path": "easy/none.rs", "type": "file_exists", "score": 1 },
+                { "name": "long-name", "path": "easy/pass.rs", "type": "name_length", "score": 2 }
+            ],
+            "normal": [],
+            "hard": [],
+            "custom_types": {
+                "file_exists": { "command": "test", "args": ["-f", "{path}"] }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert!(ExerciseConfig::load(&path).is_err());
+    let mut registry = EvaluatorRegistry::builtin();
+    registry.register("name_length", NameLength);
+    let config = ExerciseConfig::load_with(&path, &registry).unwrap();
+
+    let report = Evaluator::new(dir.path(), &config)
+        .register("name_length", NameLength)
+        .evaluate_all(&config);
+    let outcome: Vec<bool> = report.exercises.iter().map(|r| r.result).collect();
+    assert_eq!(outcome, [true, false, true]);
+    assert_eq!(report.statistics.total_score, 3);
+}
+
+// 前两次评测失败，之后通过
+struct PassesOnThirdTry(AtomicUsize);
+
+impl ExerciseEvaluator for PassesOnThirdTry {
+    fn evaluate(&self, _ctx: &mut Context) -> bool {
+        self.0.fetch_add(1, Ordering::SeqCst) >= 2
+    }
+}
+
+#[test]
+fn retries_and_marks_flaky_exercises() {
+    let dir = fixture("basic");
+    let mut config = load_config(dir.path());
+    let mut exercise = config.easy[0].clone();
+    exercise.exercise_type = "third_try".to_string();
+
+    config.retries = 1;
+    let result = Evaluator::new(dir.path(), &config)
+        .register("third_try", PassesOnThirdTry(AtomicUsize::new(0)))
+        .evaluate(&exercise);
+    assert!(!result.result);
+    assert_eq!(result.attempts.len(), 2);
+    assert!(!result.flaky);
+
+    exercise.retries = Some(3);
+    let result = Evaluator::new(dir.path(), &config)
+        .register("third_try", PassesOnThirdTry(AtomicUsize::new(0)))
+        .evaluate(&exercise);
+    assert!(result.result);
+    assert!(result.flaky);
+    let passed: Vec<bool> = result.attempts.iter().map(|a| a.passed).collect();
+    assert_eq!(passed, [false, false, true]);
+}
+
+#[test]
+fn enforces_cpu_budget() {
+    let dir = fixture("basic");
+    let config = load_config(dir.path());
+    let mut exercise = config.easy[0].clone();
+    exercise.name = "spin.rs".to_string();
+    exercise.path = "easy/spin.rs".to_string();
+
+    exercise.cpu_budget_ms = Some(50);
+    let result = Evaluator::new(dir.path(), &config).evaluate(&exercise);
+    assert!(!result.result);
+    assert!(result.cpu_budget_exceeded);
+    assert!(result.cpu_ms.unwrap() >= 250);
+    let test = result.logs.iter().find(|step| step.step == "test").unwrap();
+    assert!(test.success);
+    assert_eq!(test.cpu_ms, result.cpu_ms);
+
+    exercise.cpu_budget_ms = Some(60_000);
+    let result = Evaluator::new(dir.path(), &config).evaluate(&exercise);
+    assert!(result.result);
+    assert!(!result.cpu_budget_exceeded);
+    assert_eq!(result.attempts[0].cpu_ms, result.cpu_ms);
+}
+
+#[test]
+fn reports_differential_case_that_times_out() {
+    let dir = fixture("basic");
+    let config = load_config(dir.path());
+    let mut exercise = config.easy[4].clone();
+    exercise.name = "clamp_loop.rs".to_string();
+    exercise.path = "easy/clamp_loop.rs".to_string();
+    exercise.differential.as_mut().unwrap().timeout_ms = 500;
+
+    let start = Instant::now();
+    let result = Evaluator::new(dir.path(), &config).evaluate(&exercise);
+    assert!(start.elapsed() < Duration::from_secs(60));
+    assert!(!result.result);
+    let counterexample = result.counterexample.unwrap();
+    assert_eq!(counterexample.actual, "<timed out after 500 ms>");
+    assert!(counterexample.input.parse::<i32>().unwrap() < 0);
+    assert_eq!(counterexample.expected, "0");
+}
+
+#[test]
+fn fails_differential_test_when_driver_crashes() {
+    let dir = fixture("basic");
+    let config = load_config(dir.path());
+    let mut exercise = config.easy[4].clone();
+    exercise.name = "clamp_overflow.rs".to_string();
+    exercise.path = "easy/clamp_overflow.rs".to_string();
+
+    let result = Evaluator::new(dir.path(), &config).evaluate(&exercise);
+    assert!(!result.result);
+    assert_eq!(result.score, 0);
+    let counterexample = result.counterexample.unwrap();
+    assert_eq!(counterexample.actual, "<crashed>");
+    assert!(counterexample.input.parse::<i32>().unwrap() < 0);
+    assert_eq!(counterexample.expected, "0");
+}
+
+#[test]
+fn fails_when_required_leak_check_cannot_run() {
+    let dir = fixture("basic");
+    let config = load_config(dir.path());
+    let mut exercise = config.easy[3].clone();
+    exercise.name = "leak_abort.rs".to_string();
+    exercise.path = "easy/leak_abort.rs".to_string();
+
+    let result = Evaluator::new(dir.path(), &config).evaluate(&exercise);
+    assert!(!result.result);
+    assert!(result.leaks.is_empty());
+
+    // 只给出警告时检查失败不影响结果
+    exercise.leak_check = LeakCheck::Warn;
+    assert!(Evaluator::new(dir.path(), &config).evaluate(&exercise).result);
+}
+
+#[test]
+fn skips_leak_check_with_own_global_allocator() {
+    let dir = fixture("basic");
+    let config = load_config(dir.path());
+    let mut exercise = config.easy[3].clone();
+    exercise.name = "allocator.rs".to_string();
+    exercise.path = "easy/allocator.rs".to_string();
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&lines);
+
+    let result = Evaluator::new(dir.path(), &config)
+        .on_progress(move |event| {
+            if let Progress::Output { line, .. } = event {
+                sink.lock().unwrap().push(line);
+            }
+        })
+        .evaluate(&exercise);
+    assert!(result.result);
+    assert!(result.leaks.is_empty());
+    let lines = lines.lock().unwrap();
+    assert!(lines.iter().any(|line| line.contains("#[global_allocator]")), "{:?}", lines);
+}
+
+#[test]
+fn orders_exercises_by_prerequisites() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("exercise_config.json");
+    let config = r#"{
+        "easy": [
+            { "name": "bfs.rs", "path": "easy/bfs.rs", "type": "single_file", "score": 1,
+              "prerequisites": ["graph"] },
+            { "name": "sort.rs", "path": "easy/sort.rs", "type": "single_file", "score": 1 },
+            { "name": "graph.rs", "path": "easy/graph.rs", "type": "single_file", "score": 1,
+              "prerequisites": ["sort.rs"] }
+        ],
+        "normal": [],
+        "hard": []
+    }"#;
+    fs::write(&path, config).unwrap();
+
+    let config = ExerciseConfig::load(&path).unwrap();
+    let order: Vec<String> = config
+        .topological_order()
+        .unwrap()
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    assert_eq!(order, ["sort.rs", "graph.rs", "bfs.rs"]);
+
+    let mut passed = std::collections::HashSet::new();
+    assert_eq!(config.locked_by(&config.easy[0], &passed), ["graph.rs"]);
+    passed.insert("graph.rs".to_string());
+    assert!(config.locked_by(&config.easy[0], &passed).is_empty());
+
+    let cyclic = r#"{
+        "easy": [
+            { "name": "a", "path": "easy/a.rs", "type": "single_file", "score": 1, "prerequisites": ["c"] },
+            { "name": "b", "path": "easy/b.rs", "type": "single_file", "score": 1, "prerequisites": ["a"] },
+            { "name": "c", "path": "easy/c.rs", "type": "single_file", "score": 1, "prerequisites": ["b", "d"] }
+        ],
+        "normal": [],
+        "hard": []
+    }"#;
+    fs::write(&path, cyclic).unwrap();
+    match ExerciseConfig::load(&path) {
+        Err(ConfigError::Invalid(problems)) => {
+            assert_eq!(
+                problems,
+                [
+                    "c has unknown prerequisite d",
+                    "prerequisites form a cycle: a -> c -> b -> a",
+                ]
+            );
+        }
+        other => panic!("expected validation error, got {:?}", other.map(|_| ())),
+    }
+}
@@ -0,0 +1,22 @@
+use super::Rng;
+
+pub type Input = Vec<i32>;
+
+pub fn generate(n: u64, rng: &mut Rng) -> Input {
+    (0..n).map(|_| rng.range(0, 1000)).collect()
+}
+
+pub fn expected(_input: &Input) -> Option<i32> {
+    None
+}
+
+// 对每一对元素调用一次，耗时随 n 平方增长
+pub fn run(input: &Input) -> i32 {
+    let mut total = 0i32;
+    for a in input {
+        for b in input {
+            total = total.wrapping_add(crate::double(a ^ b));
+        }
+    }
+    total
+}
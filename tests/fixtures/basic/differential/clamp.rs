@@ -0,0 +1,23 @@
+use super::Rng;
+
+pub type Input = i32;
+
+pub fn generate(rng: &mut Rng) -> Input {
+    rng.range(-1000, 1000)
+}
+
+pub fn candidate(input: &Input) -> i32 {
+    crate::clamp_to_byte(*input)
+}
+
+pub fn reference(input: &Input) -> i32 {
+    (*input).clamp(0, 255)
+}
+
+pub fn shrink(input: &Input) -> Vec<Input> {
+    if *input < -1 {
+        vec![*input / 2]
+    } else {
+        Vec::new()
+    }
+}
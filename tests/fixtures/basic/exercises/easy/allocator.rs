@@ -0,0 +1,18 @@
+use std::alloc::System;
+
+#[global_allocator]
+static GLOBAL: System = System;
+
+pub fn make_list(n: i32) -> Vec<i32> {
+    (0..n).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_make_list() {
+        assert_eq!(make_list(3), vec![0, 1, 2]);
+    }
+}
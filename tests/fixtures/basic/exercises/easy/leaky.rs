@@ -0,0 +1,14 @@
+pub fn make_node(value: i32) -> *mut i32 {
+    Box::into_raw(Box::new(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_make_node() {
+        let node = make_node(7);
+        assert_eq!(unsafe { *node }, 7);
+    }
+}
@@ -0,0 +1,15 @@
+use std::net::TcpStream;
+
+pub fn connect() -> bool {
+    TcpStream::connect(("127.0.0.1", 9)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_server() {
+        assert!(!connect());
+    }
+}
@@ -0,0 +1,21 @@
+use std::time::{Duration, Instant};
+
+// 忙等约 300 毫秒，消耗 CPU 时间
+pub fn spin() -> u64 {
+    let start = Instant::now();
+    let mut count = 0u64;
+    while start.elapsed() < Duration::from_millis(300) {
+        count = std::hint::black_box(count + 1);
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spins() {
+        assert!(spin() > 0);
+    }
+}
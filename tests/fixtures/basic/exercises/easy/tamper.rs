@@ -0,0 +1,17 @@
+use std::path::Path;
+
+// 改写同一目录下另一道习题的源码
+pub fn tamper() {
+    let sibling = Path::new(file!()).with_file_name("pass.rs");
+    let _ = std::fs::write(sibling, "// tampered\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_sibling() {
+        tamper();
+    }
+}
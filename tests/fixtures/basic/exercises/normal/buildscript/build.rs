@@ -0,0 +1,9 @@
+use std::fs;
+use std::path::Path;
+
+// 构建脚本在编译时运行，尝试写入习题目录之外的文件
+fn main() {
+    let manifest = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let escaped = Path::new(&manifest).join("../../../escaped.txt");
+    let _ = fs::write(escaped, "written by build.rs\n");
+}
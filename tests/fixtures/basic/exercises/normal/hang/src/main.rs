@@ -0,0 +1,6 @@
+fn main() {
+    // 从不退出，也不读取标准输入
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
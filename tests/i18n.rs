@@ -0,0 +1,44 @@
+use cargotest::i18n::{Lang, Messages};
+
+#[test]
+fn selects_language_from_arguments() {
+    let mut args: Vec<String> = ["cargotest", "--lang", "zh", "hint", "algorithm1"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert_eq!(Lang::from_args(&mut args), (Lang::Zh, None));
+    assert_eq!(args, ["cargotest", "hint", "algorithm1"]);
+
+    let mut args = vec!["cargotest".to_string(), "all".to_string(), "--lang=fr".to_string()];
+    assert_eq!(Lang::from_args(&mut args), (Lang::En, Some("fr".to_string())));
+    assert_eq!(args, ["cargotest", "all"]);
+
+    assert_eq!(Lang::parse("zh_CN.UTF-8"), Some(Lang::Zh));
+    assert_eq!(Lang::parse("en_US.UTF-8"), Some(Lang::En));
+}
+
+#[test]
+fn formats_messages_with_english_fallback() {
+    let zh = Messages::new(Lang::Zh);
+    assert!(zh.missing().is_empty(), "{:?}", zh.missing());
+    assert_eq!(zh.format("hint.line", &[&1, &3, &"看看边界"]), "提示 1/3：看看边界");
+    assert_eq!(
+        Messages::new(Lang::En).format("summary.total_score", &[&100]),
+        "Total score: 100"
+    );
+    assert_eq!(zh.text("no.such.key"), "no.such.key");
+}
+
+#[test]
+fn substitutes_placeholders_in_one_pass() {
+    let en = Messages::new(Lang::En);
+    // 参数中的占位符原样保留，不会被再次替换
+    assert_eq!(
+        en.format("hint.line", &[&"{1}", &3, &"{0}"]),
+        "Hint {1}/3: {0}"
+    );
+    assert_eq!(
+        Messages::new(Lang::Zh).format("eval.test_passed", &[&"algorithm1"]),
+        "algorithm1：测试通过"
+    );
+}
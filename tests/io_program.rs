@@ -0,0 +1,140 @@
+mod common;
+
+use cargotest::io_program::{outputs_match, Compare, IoSettings};
+use cargotest::{Evaluator, ExerciseConfig, Progress};
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn settings(compare: Compare) -> IoSettings {
+    IoSettings {
+        compare,
+        ..IoSettings::default()
+    }
+}
+
+#[test]
+fn compares_exact_output_byte_for_byte() {
+    let exact = settings(Compare::Exact);
+    assert!(outputs_match("3\n", "3\n", &exact));
+    assert!(!outputs_match("3", "3\n", &exact));
+    assert!(!outputs_match("3 \n", "3\n", &exact));
+    assert!(!outputs_match("3\r\n", "3\n", &exact));
+
+    let trimmed = settings(Compare::Trimmed);
+    assert!(outputs_match("  3 \n\n", "3\n", &trimmed));
+    assert!(!outputs_match("3\n4\n", "3 4\n", &trimmed));
+}
+
+#[test]
+fn compares_floats_within_tolerance() {
+    let float = settings(Compare::Float);
+    // 远离 0 时按相对误差比较
+    assert!(outputs_match("1000000.5", "1000000", &float));
+    assert!(!outputs_match("1000002", "1000000", &float));
+    assert!(outputs_match("3.0000001 x", "3\nx", &float));
+    assert!(!outputs_match("3.00001", "3", &float));
+    // 接近 0 时按绝对误差比较
+    assert!(outputs_match("0.0000005", "0", &float));
+    assert!(outputs_match("-1e-7", "0.0", &float));
+    assert!(!outputs_match("0.001", "0", &float));
+    // 非数值按字面比较，数量不同即不一致
+    assert!(!outputs_match("3 y", "3 x", &float));
+    assert!(!outputs_match("3", "3 0", &float));
+
+    let loose = IoSettings {
+        tolerance: 0.01,
+        ..float
+    };
+    assert!(outputs_match("0.005", "0", &loose));
+    assert!(outputs_match("101", "100", &loose));
+}
+
+#[test]
+fn prints_unified_diff_for_failed_case() {
+    let dir = common::fixture("basic");
+    let root = dir.path();
+    let config = ExerciseConfig::load(root.join("exercise_config.json")).unwrap();
+    let sum = &config.normal[0];
+    fs::write(sum.source_path(root).join("io/wrong.in"), "1 2\n").unwrap();
+    fs::write(sum.source_path(root).join("io/wrong.out"), "4\n").unwrap();
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&lines);
+
+    let result = Evaluator::new(root, &config)
+        .on_progress(move |event| {
+            if let Progress::Output { line, .. } = event {
+                sink.lock().unwrap().push(line);
+            }
+        })
+        .evaluate(sum);
+    assert!(!result.result);
+
+    let lines = lines.lock().unwrap();
+    let failed = lines
+        .iter()
+        .position(|line| line.contains("case wrong"))
+        .unwrap();
+    assert!(lines[failed].contains("FAILED"), "{:?}", lines);
+    let diff = &lines[failed + 1..failed + 6];
+    assert!(
+        diff[0].starts_with("--- ") && diff[0].ends_with("wrong.out"),
+        "{:?}",
+        diff
+    );
+    assert_eq!(diff[1], "+++ actual");
+    assert!(diff[2].starts_with("@@"));
+    assert_eq!(&diff[3..], ["-4", "+3"]);
+
+    // 用例直接运行构建出的程序，而不是 cargo run
+    let case = result
+        .logs
+        .iter()
+        .find(|step| step.step == "case small")
+        .unwrap();
+    assert!(
+        case.command.ends_with("target/debug/sum"),
+        "{}",
+        case.command
+    );
+}
+
+#[test]
+fn kills_programs_that_exceed_timeout() {
+    let dir = common::fixture("basic");
+    let root = dir.path();
+    let config = ExerciseConfig::load(root.join("exercise_config.json")).unwrap();
+    let mut exercise = config.normal[0].clone();
+    exercise.name = "hang".to_string();
+    exercise.path = "normal/hang".to_string();
+    exercise.io.timeout_ms = 300;
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&lines);
+
+    let start = Instant::now();
+    let result = Evaluator::new(root, &config)
+        .on_progress(move |event| {
+            if let Progress::Output { line, .. } = event {
+                sink.lock().unwrap().push(line);
+            }
+        })
+        .evaluate(&exercise);
+    assert!(!result.result);
+    assert!(start.elapsed() < Duration::from_secs(60));
+
+    let lines = lines.lock().unwrap();
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.contains("timed out after 300 ms")),
+        "{:?}",
+        lines
+    );
+    let case = result
+        .logs
+        .iter()
+        .find(|step| step.step == "case one")
+        .unwrap();
+    assert!(!case.success);
+    assert!(case.duration_ms >= 300);
+}
@@ -0,0 +1,134 @@
+use cargotest::{signing, ConfigError, ExerciseConfig, ExerciseResult, Report};
+use std::fs;
+use std::time::Duration;
+
+fn parse_config(json: &str) -> Result<ExerciseConfig, ConfigError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("exercise_config.json");
+    fs::write(&path, json).unwrap();
+    ExerciseConfig::load(&path)
+}
+
+fn result(name: &str, passed: bool, score: i32) -> ExerciseResult {
+    ExerciseResult {
+        name: name.to_string(),
+        result: passed,
+        score: if passed { score } else { 0 },
+        hints_used: 0,
+        leaks: Vec::new(),
+        counterexample: None,
+        violations: Vec::new(),
+        complexity: None,
+        sandbox_violations: Vec::new(),
+        cpu_ms: None,
+        cpu_budget_exceeded: false,
+        timed_out: false,
+        attempts: Vec::new(),
+        flaky: false,
+        logs: Vec::new(),
+        resumed: false,
+    }
+}
+
+const CONFIG: &str = r#"{
+    "easy": [
+        { "name": "e1", "path": "easy/e1.rs", "type": "single_file", "score": 5 },
+        { "name": "e2", "path": "easy/e2.rs", "type": "single_file", "score": 5 },
+        { "name": "e3", "path": "easy/e3.rs", "type": "single_file", "score": 5 }
+    ],
+    "normal": [
+        { "name": "n1", "path": "normal/n1", "type": "cargo_project", "score": 10 },
+        { "name": "n2", "path": "normal/n2", "type": "cargo_project", "score": 10 }
+    ],
+    "hard": [
+        { "name": "h1", "path": "hard/h1", "type": "cargo_project", "score": 30 }
+    ],
+    "categories": {
+        "easy": { "max_score": 15, "cap": 10 },
+        "normal": { "max_score": 20, "min_passed": 2 },
+        "hard": { "max_score": 30 }
+    }
+}"#;
+
+#[test]
+fn applies_category_policies() {
+    let config = parse_config(CONFIG).unwrap();
+    let mut report = Report::new();
+    for r in [
+        result("e1", true, 5),
+        result("e2", true, 5),
+        result("e3", true, 5),
+        result("n1", true, 10),
+        result("n2", false, 10),
+        result("h1", true, 30),
+    ] {
+        report.record(r);
+    }
+    report.finish(&config, Duration::from_secs(3));
+
+    let summary: Vec<(&str, i32, i32, i32, bool)> = report
+        .categories
+        .iter()
+        .map(|c| (c.name.as_str(), c.raw_score, c.score, c.max_score, c.requirement_met))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("easy", 15, 10, 10, true),
+            ("normal", 10, 0, 20, false),
+            ("hard", 30, 30, 30, true),
+        ]
+    );
+    assert_eq!(report.categories[2].percentage, 100.0);
+    assert_eq!(report.statistics.total_score, 40);
+    assert_eq!(report.statistics.total_time, 3);
+}
+
+#[test]
+fn rejects_scores_that_do_not_match_category_maximum() {
+    let config = CONFIG
+        .replace(r#""max_score": 30"#, r#""max_score": 50"#)
+        .replace(r#""min_passed": 2"#, r#""min_passed": 3"#);
+    match parse_config(&config) {
+        Err(ConfigError::Invalid(problems)) => {
+            assert_eq!(problems.len(), 2, "{:?}", problems);
+            assert!(problems.iter().any(|p| p.contains("hard scores add up to 30")));
+            assert!(problems.iter().any(|p| p.contains("normal requires 3")));
+        }
+        other => panic!("expected validation error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn signed_report_detects_tampering() {
+    let root = tempfile::tempdir().unwrap();
+    let source = root.path().join("exercises/easy/e1.rs");
+    fs::create_dir_all(source.parent().unwrap()).unwrap();
+    fs::write(&source, "pub fn answer() -> i32 { 42 }\n").unwrap();
+
+    let config = parse_config(CONFIG).unwrap();
+    let key = b"course secret";
+    let mut report = Report::new();
+    report.record(result("e1", true, 5));
+    report.finish(&config, Duration::from_secs(1));
+    signing::sign(&mut report, root.path(), &config, key).unwrap();
+
+    let saved = root.path().join("report.json");
+    report.save(&saved).unwrap();
+    let loaded = Report::load(&saved).unwrap();
+    assert!(signing::verify(&loaded, root.path(), &config, key).unwrap().is_valid());
+
+    let wrong_key = signing::verify(&loaded, root.path(), &config, b"other").unwrap();
+    assert!(!wrong_key.key_matches);
+    assert!(!wrong_key.signature_valid);
+
+    let mut edited = Report::load(&saved).unwrap();
+    edited.statistics.total_score = 100;
+    let verification = signing::verify(&edited, root.path(), &config, key).unwrap();
+    assert!(!verification.signature_valid);
+
+    fs::write(&source, "pub fn answer() -> i32 { 41 }\n").unwrap();
+    let verification = signing::verify(&loaded, root.path(), &config, key).unwrap();
+    assert!(verification.signature_valid);
+    assert_eq!(verification.changed_sources, ["exercises/easy/e1.rs"]);
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use cargotest::{reset, ExerciseConfig};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn git(root: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(root)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+// 把夹具提交为上游版本
+fn commit_baseline(root: &Path) {
+    git(root, &["init", "-q"]);
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "baseline"]);
+}
+
+// 把当前提交记为上游分支，之后的提交视为学生的提交
+fn mark_upstream(root: &Path) {
+    git(root, &["update-ref", "refs/remotes/upstream/main", "HEAD"]);
+}
+
+#[test]
+fn takes_templates_from_git_baseline() {
+    let dir = common::fixture("basic");
+    let root = dir.path();
+    let config = ExerciseConfig::load(root.join("exercise_config.json")).unwrap();
+    let exercise = &config.easy[1];
+    let source = exercise.source_path(root);
+    let pristine = fs::read_to_string(&source).unwrap();
+    commit_baseline(root);
+    mark_upstream(root);
+
+    // 学生在首次运行前修改并提交的内容不应成为模板
+    fs::write(&source, pristine.replace("fn ", "fn edited_")).unwrap();
+    git(root, &["commit", "-q", "-am", "student work"]);
+    reset::snapshot_templates(root, &config.all_exercises()).unwrap();
+    assert!(reset::has_template(root, exercise));
+
+    let diffs = reset::pending_changes(root, exercise).unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert!(diffs[0].contains("-pub fn edited_double") && diffs[0].contains("+pub fn double"));
+
+    reset::restore(root, exercise).unwrap();
+    assert_eq!(fs::read_to_string(&source).unwrap(), pristine);
+    assert!(reset::pending_changes(root, exercise).unwrap().is_empty());
+}
+
+#[test]
+fn takes_templates_from_latest_upstream_commit() {
+    let dir = common::fixture("basic");
+    let root = dir.path();
+    let config = ExerciseConfig::load(root.join("exercise_config.json")).unwrap();
+    let exercise = &config.easy[1];
+    let source = exercise.source_path(root);
+    let original = fs::read_to_string(&source).unwrap();
+    commit_baseline(root);
+
+    // 上游在首次提交之后更新了模板
+    let updated = format!("{}// upstream update\n", original);
+    fs::write(&source, &updated).unwrap();
+    git(root, &["commit", "-q", "-am", "upstream update"]);
+    mark_upstream(root);
+    fs::write(&source, "// student work\n").unwrap();
+    git(root, &["commit", "-q", "-am", "student work"]);
+    reset::snapshot_templates(root, &config.all_exercises()).unwrap();
+    reset::restore(root, exercise).unwrap();
+    assert_eq!(fs::read_to_string(&source).unwrap(), updated);
+
+    // 学生合并了上游的新版本后，缓存的模板随之刷新
+    let newer = format!("{}// another update\n", original);
+    git(root, &["checkout", "-q", "--", "."]);
+    git(root, &["checkout", "-q", "-b", "next", "upstream/main"]);
+    fs::write(&source, &newer).unwrap();
+    git(root, &["commit", "-q", "-am", "another update"]);
+    mark_upstream(root);
+    git(root, &["checkout", "-q", "-"]);
+    git(
+        root,
+        &[
+            "merge",
+            "-q",
+            "-X",
+            "theirs",
+            "-m",
+            "merge upstream",
+            "upstream/main",
+        ],
+    );
+    reset::snapshot_templates(root, &config.all_exercises()).unwrap();
+    fs::write(&source, "// student work\n").unwrap();
+    reset::restore(root, exercise).unwrap();
+    assert_eq!(fs::read_to_string(&source).unwrap(), newer);
+}
+
+#[test]
+fn takes_templates_from_head_without_remotes() {
+    let dir = common::fixture("basic");
+    let root = dir.path();
+    let config = ExerciseConfig::load(root.join("exercise_config.json")).unwrap();
+    let exercise = &config.easy[1];
+    let source = exercise.source_path(root);
+    commit_baseline(root);
+
+    // 没有配置远程的仓库只有上游的提交，模板取最新提交
+    let updated = format!(
+        "{}// upstream update\n",
+        fs::read_to_string(&source).unwrap()
+    );
+    fs::write(&source, &updated).unwrap();
+    git(root, &["commit", "-q", "-am", "upstream update"]);
+    reset::snapshot_templates(root, &config.all_exercises()).unwrap();
+    fs::write(&source, "// local edit\n").unwrap();
+    reset::restore(root, exercise).unwrap();
+    assert_eq!(fs::read_to_string(&source).unwrap(), updated);
+}
+
+#[test]
+fn restores_project_and_removes_added_files() {
+    let dir = common::fixture("basic");
+    let root = dir.path();
+    let config = ExerciseConfig::load(root.join("exercise_config.json")).unwrap();
+    let project = &config.normal[0];
+    commit_baseline(root);
+    reset::snapshot_templates(root, &config.all_exercises()).unwrap();
+
+    let source = project.source_path(root);
+    let main = source.join("src/main.rs");
+    let pristine = fs::read_to_string(&main).unwrap();
+    fs::write(&main, "fn main() {}\n").unwrap();
+    fs::write(source.join("src/extra.rs"), "// added\n").unwrap();
+    assert_eq!(reset::pending_changes(root, project).unwrap().len(), 2);
+
+    reset::restore(root, project).unwrap();
+    assert_eq!(fs::read_to_string(&main).unwrap(), pristine);
+    assert!(!source.join("src/extra.rs").exists());
+    assert!(source.join("io/small.in").is_file());
+    assert!(reset::pending_changes(root, project).unwrap().is_empty());
+}
+
+#[test]
+fn records_no_template_without_git_history() {
+    let dir = common::fixture("basic");
+    let root = dir.path();
+    let config = ExerciseConfig::load(root.join("exercise_config.json")).unwrap();
+    reset::snapshot_templates(root, &config.all_exercises()).unwrap();
+    assert!(!reset::has_template(root, &config.easy[0]));
+}
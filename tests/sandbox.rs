@@ -0,0 +1,140 @@
+mod common;
+
+use cargotest::sandbox::Sandbox;
+use cargotest::{Evaluator, Exercise, ExerciseConfig};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn exercise(config: &ExerciseConfig, path: &str) -> Exercise {
+    let mut exercise = config.easy[0].clone();
+    exercise.name = path.to_string();
+    exercise.path = path.to_string();
+    exercise.sandbox = Some(true);
+    exercise
+}
+
+// 当前环境不允许创建用户命名空间时跳过
+fn available(root: &Path, exercise: &Exercise) -> bool {
+    let mut command = Command::new("true");
+    let spawned = Sandbox::for_exercise(root, exercise)
+        .and_then(|sandbox| sandbox.apply(&mut command))
+        .and_then(|_| command.status());
+    match spawned {
+        Ok(status) => status.success(),
+        Err(e) => {
+            eprintln!("skipping sandbox test: {}", e);
+            false
+        }
+    }
+}
+
+#[test]
+fn hides_files_outside_the_exercise() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let escape = exercise(&config, "easy/escape.rs");
+    if !available(dir.path(), &escape) {
+        return;
+    }
+
+    let mut evaluator = Evaluator::new(dir.path(), &config);
+    assert!(
+        evaluator
+            .evaluate(&exercise(&config, "easy/pass.rs"))
+            .result
+    );
+    let result = evaluator.evaluate(&escape);
+    assert!(result.result);
+    assert!(result.sandbox_violations.is_empty());
+
+    let mut unconfined = escape.clone();
+    unconfined.sandbox = Some(false);
+    assert!(!evaluator.evaluate(&unconfined).result);
+
+    let mut sum = config.normal[0].clone();
+    sum.sandbox = Some(true);
+    assert!(evaluator.evaluate(&sum).result);
+}
+
+#[test]
+fn reports_blocked_network_access() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let mut network = exercise(&config, "easy/network.rs");
+    if !available(dir.path(), &network) {
+        return;
+    }
+
+    let result = Evaluator::new(dir.path(), &config).evaluate(&network);
+    assert!(!result.result);
+    assert_eq!(result.sandbox_violations.len(), 1);
+    assert!(result.sandbox_violations[0].starts_with("test: killed by SIGSYS"));
+    let test = result.logs.iter().find(|step| step.step == "test").unwrap();
+    assert!(test.violation.is_some());
+
+    network.sandbox = Some(false);
+    assert!(
+        Evaluator::new(dir.path(), &config)
+            .evaluate(&network)
+            .result
+    );
+}
+
+#[test]
+fn measures_cpu_time_inside_sandbox() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let spin = exercise(&config, "easy/spin.rs");
+    if !available(dir.path(), &spin) {
+        return;
+    }
+
+    let result = Evaluator::new(dir.path(), &config).evaluate(&spin);
+    assert!(result.result);
+    assert!(result.cpu_ms.unwrap() >= 250);
+}
+
+#[test]
+fn runs_build_scripts_inside_sandbox() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let mut build = config.normal[0].clone();
+    build.name = "buildscript".to_string();
+    build.path = "normal/buildscript".to_string();
+    build.sandbox = Some(true);
+    if !available(dir.path(), &build) {
+        return;
+    }
+
+    // 构建脚本无法写入习题目录之外，但不影响构建本身
+    let escaped = dir.path().join("escaped.txt");
+    assert!(Evaluator::new(dir.path(), &config).evaluate(&build).result);
+    assert!(!escaped.exists());
+
+    let dir = common::fixture("basic");
+    let escaped = dir.path().join("escaped.txt");
+    build.sandbox = Some(false);
+    assert!(Evaluator::new(dir.path(), &config).evaluate(&build).result);
+    assert!(escaped.exists());
+}
+
+#[test]
+fn keeps_sibling_exercises_out_of_reach() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let mut tamper = exercise(&config, "easy/tamper.rs");
+    if !available(dir.path(), &tamper) {
+        return;
+    }
+
+    // 沙箱中编译和运行的是私有副本，同一目录下的其他习题不可写
+    let sibling = dir.path().join("exercises/easy/pass.rs");
+    let original = fs::read_to_string(&sibling).unwrap();
+    assert!(Evaluator::new(dir.path(), &config).evaluate(&tamper).result);
+    assert_eq!(fs::read_to_string(&sibling).unwrap(), original);
+
+    tamper.sandbox = Some(false);
+    assert!(Evaluator::new(dir.path(), &config).evaluate(&tamper).result);
+    assert_eq!(fs::read_to_string(&sibling).unwrap(), "// tampered\n");
+}
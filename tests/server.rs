@@ -0,0 +1,112 @@
+mod common;
+
+use cargotest::server::Server;
+use cargotest::ExerciseConfig;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+
+fn request(addr: SocketAddr, method: &str, path: &str) -> (String, String) {
+    let host = format!("Host: {}\r\n", addr);
+    request_with(addr, method, path, &host)
+}
+
+fn request_with(addr: SocketAddr, method: &str, path: &str, headers: &str) -> (String, String) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\n{}\r\n", method, path, headers).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), body.to_string())
+}
+
+#[test]
+fn serves_report_and_streams_reevaluation() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let server = Server::bind("127.0.0.1:0", dir.path(), config).unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+
+    let (status, body) = request(addr, "GET", "/");
+    assert!(status.contains("200"), "{}", status);
+    assert!(body.contains(r#"data-name="pass.rs""#));
+
+    let (status, body) = request(addr, "GET", "/report");
+    assert!(status.contains("200"), "{}", status);
+    assert!(body.contains(r#""total_exercises": 0"#));
+
+    let (status, _) = request(addr, "POST", "/evaluate/missing");
+    assert!(status.contains("404"), "{}", status);
+
+    let mut events = TcpStream::connect(addr).unwrap();
+    write!(events, "GET /events HTTP/1.1\r\n\r\n").unwrap();
+    let mut events = BufReader::new(events);
+    let mut line = String::new();
+    while line != "\r\n" {
+        line.clear();
+        events.read_line(&mut line).unwrap();
+    }
+
+    let (status, _) = request(addr, "POST", "/evaluate/pass");
+    assert!(status.contains("202"), "{}", status);
+
+    let mut received = Vec::new();
+    loop {
+        line.clear();
+        events.read_line(&mut line).unwrap();
+        if let Some(event) = line.strip_prefix("event: ") {
+            received.push(event.trim().to_string());
+            if event.trim() == "report" {
+                break;
+            }
+        }
+    }
+    assert_eq!(received.first().unwrap(), "started");
+    assert!(received.contains(&"output".to_string()));
+    assert!(received.contains(&"finished".to_string()));
+
+    let (_, body) = request(addr, "GET", "/report");
+    let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(report["exercises"][0]["name"], "pass.rs");
+    assert_eq!(report["exercises"][0]["result"], true);
+    assert_eq!(report["statistics"]["total_score"], 1);
+    assert!(dir.path().join("report.json").exists());
+}
+
+#[test]
+fn rejects_cross_origin_evaluation_requests() {
+    let dir = common::fixture("basic");
+    let mut config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    config.easy[0].name = "it's.rs".to_string();
+    let server = Server::bind("127.0.0.1:0", dir.path(), config).unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+
+    // 习题名只出现在 data-name 属性中，不会拼接进脚本
+    let (_, body) = request(addr, "GET", "/");
+    assert!(body.contains(r#"<tr data-name="it's.rs">"#));
+    assert!(!body.contains("onclick"));
+
+    let forged = format!("Host: {}\r\nOrigin: http://evil.example\r\n", addr);
+    let (status, _) = request_with(addr, "POST", "/evaluate/missing", &forged);
+    assert!(status.contains("403"), "{}", status);
+
+    // DNS 重绑定时 Host 是攻击者的域名
+    let rebound = format!(
+        "Host: evil.example:{0}\r\nOrigin: http://evil.example:{0}\r\n",
+        addr.port()
+    );
+    let (status, _) = request_with(addr, "POST", "/evaluate/missing", &rebound);
+    assert!(status.contains("403"), "{}", status);
+
+    let (status, _) = request_with(addr, "POST", "/evaluate/missing", "");
+    assert!(status.contains("403"), "{}", status);
+
+    let own = format!(
+        "Host: localhost:{0}\r\nOrigin: http://localhost:{0}\r\n",
+        addr.port()
+    );
+    let (status, _) = request_with(addr, "POST", "/evaluate/missing", &own);
+    assert!(status.contains("404"), "{}", status);
+}
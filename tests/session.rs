@@ -0,0 +1,84 @@
+mod common;
+
+use cargotest::session::SessionState;
+use cargotest::{Evaluator, ExerciseConfig, Report};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn reuses_results_until_sources_change() {
+    let dir = common::fixture("basic");
+    let root = dir.path();
+    let mut config = ExerciseConfig::load(root.join("exercise_config.json")).unwrap();
+    let pass = config.easy[0].clone();
+    let fail = config.easy[1].clone();
+
+    let mut session = SessionState::load(root, &config).unwrap();
+    let mut evaluator = Evaluator::new(root, &config);
+    for exercise in [&pass, &fail] {
+        let result = evaluator.evaluate(exercise);
+        session.store(root, exercise, &result).unwrap();
+    }
+
+    let session = SessionState::load(root, &config).unwrap();
+    assert!(session.lookup(root, &pass).unwrap().result);
+    assert!(!session.lookup(root, &fail).unwrap().result);
+    assert!(session.lookup(root, &config.easy[2]).is_none());
+
+    let source = fail.source_path(root);
+    let text = fs::read_to_string(&source).unwrap();
+    fs::write(&source, text + "\n// edited\n").unwrap();
+    assert!(session.lookup(root, &fail).is_none());
+    assert!(session.lookup(root, &pass).is_some());
+
+    config.hint_penalty = 1;
+    let session = SessionState::load(root, &config).unwrap();
+    assert!(session.lookup(root, &pass).is_none());
+}
+
+fn run_all_resume(root: &Path) -> Report {
+    let status = Command::new(env!("CARGO_BIN_EXE_cargotest"))
+        .args(["all", "--resume"])
+        .current_dir(root)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success());
+    Report::load(root.join("report.json")).unwrap()
+}
+
+#[test]
+fn resumes_from_first_exercise_not_passed() {
+    let dir = common::fixture("basic");
+    let root = dir.path();
+    let path = root.join("exercise_config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let mut again = config["easy"][0].clone();
+    again["name"] = "again.rs".into();
+    let easy = config["easy"].as_array_mut().unwrap();
+    easy.truncate(2);
+    easy.push(again);
+    config["normal"] = serde_json::json!([]);
+    fs::write(&path, config.to_string()).unwrap();
+
+    let first = run_all_resume(root);
+    assert!(first.exercises.iter().all(|result| !result.resumed));
+
+    // 只跳过第一道未通过的题目之前的已通过题目，之后的题目全部重新评测
+    let second = run_all_resume(root);
+    let outcome: Vec<(&str, bool, bool)> = second
+        .exercises
+        .iter()
+        .map(|result| (result.name.as_str(), result.result, result.resumed))
+        .collect();
+    assert_eq!(
+        outcome,
+        [
+            ("pass.rs", true, true),
+            ("fail.rs", false, false),
+            ("again.rs", true, false)
+        ]
+    );
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use cargotest::similarity::{compare_submissions, normalize, to_csv};
+use cargotest::ExerciseConfig;
+use std::fs;
+use std::path::Path;
+
+const ORIGINAL: &str = r#"
+/// Sum of the even numbers.
+pub fn even_sum(nums: &[i32]) -> i32 {
+    let mut total = 0;
+    for n in nums {
+        if n % 2 == 0 {
+            total += n;
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn sums() {}
+}
+"#;
+
+// 只改了名字、注释和空白
+const RENAMED: &str = r#"
+// my own solution!
+pub fn sum_of_evens(values: &[i32]) -> i32 {
+    let mut acc = 0;   for v in values { if v % 2 == 0 { acc += v; } }
+    acc
+}
+"#;
+
+const DIFFERENT: &str = r#"
+pub fn even_sum(nums: &[i32]) -> i32 {
+    nums.iter().filter(|n| *n % 2 == 0).sum()
+}
+"#;
+
+fn submit(dir: &Path, student: &str, source: &str) {
+    let path = dir.join(student).join("exercises/easy/pass.rs");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, source).unwrap();
+}
+
+#[test]
+fn normalizes_names_comments_and_tests() {
+    assert_eq!(normalize(ORIGINAL).unwrap(), normalize(RENAMED).unwrap());
+    assert_ne!(normalize(ORIGINAL).unwrap(), normalize(DIFFERENT).unwrap());
+}
+
+#[test]
+fn ranks_similar_submissions() {
+    let root = common::fixture("basic");
+    let config = ExerciseConfig::load(root.path().join("exercise_config.json")).unwrap();
+    let cohort = tempfile::tempdir().unwrap();
+    submit(cohort.path(), "alice", ORIGINAL);
+    submit(cohort.path(), "bob", RENAMED);
+    submit(cohort.path(), "carol", DIFFERENT);
+
+    let results = compare_submissions(root.path(), cohort.path(), &config.easy[..1], 0.5).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].submissions, 3);
+    assert_eq!(results[0].pairs.len(), 1);
+    assert_eq!(results[0].pairs[0].first, "alice");
+    assert_eq!(results[0].pairs[0].second, "bob");
+    assert_eq!(results[0].pairs[0].score, 1.0);
+    assert_eq!(
+        to_csv(&results),
+        "exercise,first,second,similarity\npass.rs,alice,bob,1.000\n"
+    );
+}
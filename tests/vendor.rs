@@ -0,0 +1,77 @@
+mod common;
+
+use cargotest::vendor;
+use cargotest::ExerciseConfig;
+use std::fs;
+
+#[test]
+fn reports_exercises_that_cannot_build_offline() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let exercises = config.all_exercises();
+    let cargo_exercises = vendor::cargo_exercises(dir.path(), &exercises);
+    assert_eq!(cargo_exercises.len(), 1);
+    assert_eq!(cargo_exercises[0].0, "sum");
+    assert!(vendor::preflight(dir.path(), &exercises)
+        .unwrap()
+        .is_empty());
+
+    let manifest = &cargo_exercises[0].1;
+    let mut text = fs::read_to_string(manifest).unwrap();
+    text.push_str("cargotest-no-such-crate = \"1\"\n");
+    fs::write(manifest, text).unwrap();
+
+    let issues = vendor::preflight(dir.path(), &exercises).unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].exercise, "sum");
+    assert!(
+        issues[0].reason.contains("cargotest-no-such-crate"),
+        "{}",
+        issues[0].reason
+    );
+}
+
+#[test]
+fn skips_configuration_without_dependencies() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let exercises = config.all_exercises();
+
+    assert_eq!(
+        vendor::vendor(dir.path(), &exercises, "vendor", true).unwrap(),
+        0
+    );
+    assert!(!dir.path().join(vendor::CONFIG_FILE).exists());
+    assert!(vendor::preflight(dir.path(), &exercises)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn ignores_packages_only_in_local_cargo_cache() {
+    let dir = common::fixture("basic");
+    let config = ExerciseConfig::load(dir.path().join("exercise_config.json")).unwrap();
+    let exercises = config.all_exercises();
+    let cargo_exercises = vendor::cargo_exercises(dir.path(), &exercises);
+    let manifest = &cargo_exercises[0].1;
+    // 评测程序自身的依赖，本机 Cargo 缓存中必然已有
+    let mut text = fs::read_to_string(manifest).unwrap();
+    text.push_str("hex = \"0.4\"\n");
+    fs::write(manifest, text).unwrap();
+
+    // 缓存中已有，但尚未下载到仓库中
+    let issues = vendor::preflight(dir.path(), &exercises).unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].exercise, "sum");
+
+    assert!(vendor::vendor(dir.path(), &exercises, "vendor", true).unwrap() > 0);
+    assert!(vendor::preflight(dir.path(), &exercises)
+        .unwrap()
+        .is_empty());
+
+    // 配置仍指向源码目录，但目录已被删除
+    fs::remove_dir_all(dir.path().join("vendor")).unwrap();
+    let issues = vendor::preflight(dir.path(), &exercises).unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].exercise, "sum");
+}